@@ -1,16 +1,168 @@
-use std::thread;
-use std::io::Write;
-use std::net::TcpListener;
+extern crate env_logger;
+
+// Simulator
+extern crate rise;
+use rise::neat::Creator;
+use rise::neat::benchmarks::{CartPole, Regression, Xor};
+use rise::neat::config::Config;
+use rise::neat::distributed::{self, Coordinator};
+use rise::neat::population::Population;
+use rise::neat::task::Task;
+
+use std::env;
+use std::fs::File;
+use std::process;
+use std::time::Duration;
+
+const USAGE: &'static str = "Usage: server [options]
+Hands genomes out to workers and evolves them with the fitness the workers send back. Workers are
+started with --worker and can run on any machine that can reach the coordinator.
+    --bind ADDR          address the coordinator listens on (127.0.0.1:8080)
+    --worker ADDR        run as a worker for the coordinator at ADDR instead
+    --task NAME          xor, pole, pole-nv, double-pole, double-pole-nv or sine (xor)
+    --generations N      number of generations to run (100)
+    --population N       number of genomes (100)
+    --timeout MS         how long a worker gets per genome before its job is reassigned (10000)
+    --patience MS        how long to wait with no workers connected before giving up (60000)
+    --seed N             seed for the random number generator
+    --config PATH        evolution settings, see neat::config::Config::parse
+    --output PATH        where the champion genome is saved (champion.genome)";
+
+struct Options {
+    bind: String,
+    worker: Option<String>,
+    task: String,
+    generations: u64,
+    population: usize,
+    timeout: u64,
+    patience: u64,
+    seed: Option<usize>,
+    config: Option<String>,
+    output: String
+}
+
+impl Options {
+    fn parse() -> Result<Options, String> {
+        let mut options = Options {
+            bind: "127.0.0.1:8080".to_string(),
+            worker: None,
+            task: "xor".to_string(),
+            generations: 100,
+            population: 100,
+            timeout: 10000,
+            patience: 60000,
+            seed: None,
+            config: None,
+            output: "champion.genome".to_string()
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(flag) = args.next() {
+            let value = try!(args.next().ok_or(format!("Missing value for {}", flag)));
+            let bad_value = format!("Bad value for {}: {}", flag, value);
+            match flag.as_ref() {
+                "--bind" => options.bind = value,
+                "--worker" => options.worker = Some(value),
+                "--task" => options.task = value,
+                "--generations" => options.generations = try!(value.parse().map_err(|_| bad_value)),
+                "--population" => options.population = try!(value.parse().map_err(|_| bad_value)),
+                "--timeout" => options.timeout = try!(value.parse().map_err(|_| bad_value)),
+                "--patience" => options.patience = try!(value.parse().map_err(|_| bad_value)),
+                "--seed" => options.seed = Some(try!(value.parse().map_err(|_| bad_value))),
+                "--config" => options.config = Some(value),
+                "--output" => options.output = value,
+                _ => return Err(format!("Unknown option {}", flag))
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn task(name: &str) -> Option<Box<Task>> {
+    match name {
+        "xor" => Some(Box::new(Xor)),
+        "pole" => Some(Box::new(CartPole::single(true))),
+        "pole-nv" => Some(Box::new(CartPole::single(false))),
+        "double-pole" => Some(Box::new(CartPole::double(true))),
+        "double-pole-nv" => Some(Box::new(CartPole::double(false))),
+        "sine" => Some(Box::new(Regression::sine())),
+        _ => None
+    }
+}
+
+fn coordinate(options: &Options, task: &Task) {
+    let timeout = Duration::from_millis(options.timeout);
+    let mut coordinator = Coordinator::bind(&options.bind[..], timeout).unwrap_or_else(|e| {
+        println!("Couldn't listen on {}: {}", options.bind, e);
+        process::exit(1);
+    });
+    coordinator.set_patience(Duration::from_millis(options.patience));
+    println!("Coordinating {} on {}, waiting for workers", options.task, coordinator.local_addr());
+
+    let mut config = match options.config {
+        Some(ref path) => Config::load(path).unwrap_or_else(|e| {
+            println!("Couldn't load config {}: {}", path, e);
+            process::exit(1);
+        }),
+        None => Config::new()
+    };
+    if options.seed.is_some() {
+        config.seed = options.seed;
+    }
+
+    let mut creator = Creator::with_config(config);
+    let genomes = creator.random_genomes(options.population, task.num_inputs(), task.num_outputs());
+    let mut population = Population::from_genomes(genomes, creator);
+
+    for _ in 0..options.generations {
+        if let Err(e) = coordinator.evaluate(&mut population.genomes) {
+            println!("Evaluation stopped: {}", e);
+            break;
+        }
+        let stats = population.reproduce();
+        println!("gen {:5}  best {:12.4}  mean {:12.4}  species {:3}  idle {:3}",
+                 stats.generation, stats.max_fitness, stats.mean_fitness, stats.species.len(),
+                 coordinator.idle_workers());
+    }
+
+    match population.creator.hall_of_fame.best() {
+        Some(entry) => {
+            let saved = File::create(&options.output)
+                .and_then(|mut file| entry.genome.write(&mut file));
+            match saved {
+                Ok(_) => println!("Champion with fitness {} saved to {}", entry.fitness, options.output),
+                Err(e) => println!("Couldn't save champion to {}: {}", options.output, e)
+            }
+        },
+        None => println!("No champion to save")
+    }
+}
 
 fn main() {
-    let listener = TcpListener::bind("127.0.0.1:8080").unwrap();
-    println!("listening started, ready to accept");
-
-    // accept connections and process them, spawning a new thread for each one
-    for stream in listener.incoming() {
-        thread::spawn(|| {
-            let mut stream = stream.unwrap();
-            stream.write(b"Hello World\r\n").unwrap();
-        });
+    let _ = env_logger::init();
+
+    let options = match Options::parse() {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}\n{}", e, USAGE);
+            process::exit(1);
+        }
+    };
+
+    let mut task = task(&options.task).unwrap_or_else(|| {
+        println!("Unknown task {}\n{}", options.task, USAGE);
+        process::exit(1);
+    });
+
+    match options.worker {
+        Some(ref addr) => match distributed::work(&addr[..], &mut *task) {
+            Ok(jobs) => println!("Coordinator finished, evaluated {} genomes", jobs),
+            Err(e) => {
+                println!("Worker failed: {}", e);
+                process::exit(1);
+            }
+        },
+        None => coordinate(&options, &*task)
     }
 }
@@ -0,0 +1,228 @@
+extern crate env_logger;
+#[cfg(unix)]
+extern crate libc;
+
+// Simulator
+extern crate rise;
+use rise::{World, SPG};
+use rise::config::WorldConfig;
+use rise::neat::Creator;
+use rise::neat::benchmarks::{CartPole, Regression, Xor};
+use rise::neat::checkpoint;
+use rise::neat::config::Config;
+use rise::neat::control::{Evolve, RunController, StoppingCriteria, TaskRun};
+use rise::neat::genetics::Genome;
+use rise::neat::hall_of_fame::HallOfFame;
+use rise::neat::task::Task;
+
+use std::env;
+use std::fs::File;
+use std::process;
+
+const USAGE: &'static str = "Usage: train [options]
+    --task NAME          world, xor, pole, pole-nv, double-pole, double-pole-nv or sine (world)
+    --generations N      stop after this many generations (100)
+    --target FITNESS     stop once a genome reaches this fitness
+    --stagnation N       stop after N generations without a better genome
+    --time-budget SECS   stop once this much time has passed
+    --population N       number of genomes (100)
+    --seconds SECS       simulated seconds per world generation (10)
+    --seed N             seed for the random number generator
+    --config PATH        evolution settings, see neat::config::Config::parse
+    --checkpoint DIR     where the final population and hall of fame are saved (checkpoint)
+    --output PATH        where the champion genome is saved (champion.genome)";
+
+// Stops the run cleanly on Ctrl-C so the champion and pool still get written
+#[cfg(unix)]
+mod interrupt {
+    use libc;
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn handle(_: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    pub fn install() {
+        let handler: extern "C" fn(libc::c_int) = handle;
+        unsafe { libc::signal(libc::SIGINT, handler as libc::sighandler_t); }
+    }
+
+    pub fn interrupted() -> bool {
+        INTERRUPTED.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(not(unix))]
+mod interrupt {
+    pub fn install() {}
+
+    pub fn interrupted() -> bool {
+        false
+    }
+}
+
+struct Options {
+    task: String,
+    criteria: StoppingCriteria,
+    population: usize,
+    generation_seconds: f64,
+    seed: Option<usize>,
+    config: Option<String>,
+    checkpoint: String,
+    output: String
+}
+
+impl Options {
+    fn parse() -> Result<Options, String> {
+        let mut criteria = StoppingCriteria::new();
+        criteria.max_generations = Some(100);
+        let mut options = Options {
+            task: "world".to_string(),
+            criteria: criteria,
+            population: 100,
+            generation_seconds: SPG,
+            seed: None,
+            config: None,
+            checkpoint: "checkpoint".to_string(),
+            output: "champion.genome".to_string()
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(flag) = args.next() {
+            let value = try!(args.next().ok_or(format!("Missing value for {}", flag)));
+            if try!(options.criteria.parse_flag(&flag, &value)) {
+                continue;
+            }
+
+            let bad_value = format!("Bad value for {}: {}", flag, value);
+            match flag.as_ref() {
+                "--task" => options.task = value,
+                "--population" => options.population = try!(value.parse().map_err(|_| bad_value)),
+                "--seconds" =>
+                    options.generation_seconds = try!(value.parse().map_err(|_| bad_value)),
+                "--seed" => options.seed = Some(try!(value.parse().map_err(|_| bad_value))),
+                "--config" => options.config = Some(value),
+                "--checkpoint" => options.checkpoint = value,
+                "--output" => options.output = value,
+                _ => return Err(format!("Unknown option {}", flag))
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+// Something evolving that can be checkpointed
+trait Run: Evolve {
+    fn pool(&self) -> Vec<&Genome>;
+    fn hall_of_fame(&self) -> &HallOfFame;
+}
+
+impl Run for World {
+    fn pool(&self) -> Vec<&Genome> {
+        self.genomes()
+    }
+
+    fn hall_of_fame(&self) -> &HallOfFame {
+        &self.creator().hall_of_fame
+    }
+}
+
+impl<T: Task> Run for TaskRun<T> {
+    fn pool(&self) -> Vec<&Genome> {
+        self.population.genomes.iter().collect()
+    }
+
+    fn hall_of_fame(&self) -> &HallOfFame {
+        &self.population.creator.hall_of_fame
+    }
+}
+
+fn task_run<T: Task + 'static>(task: T, size: usize, creator: Creator) -> Box<Run> {
+    Box::new(TaskRun::new(task, size, creator))
+}
+
+fn main() {
+    let _ = env_logger::init();
+
+    let options = match Options::parse() {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}\n{}", e, USAGE);
+            process::exit(1);
+        }
+    };
+
+    let config = match options.config {
+        Some(ref path) => Config::load(path).unwrap_or_else(|e| {
+            println!("Couldn't load config {}: {}", path, e);
+            process::exit(1);
+        }),
+        None => Config::new()
+    };
+
+    let mut config = config;
+    if options.seed.is_some() {
+        config.seed = options.seed;
+    }
+
+    let creator = Creator::with_config(config);
+    let size = options.population;
+    let mut run: Box<Run> = match options.task.as_ref() {
+        "world" => {
+            let mut world_config = WorldConfig::new();
+            world_config.population = size;
+            world_config.generation_seconds = options.generation_seconds;
+            // Headless, so ticks run as fast as they can
+            println!("Running world with generations of {} ticks", world_config.ticks_per_generation());
+            Box::new(World::with_config(world_config, creator))
+        },
+        "xor" => task_run(Xor, size, creator),
+        "pole" => task_run(CartPole::single(true), size, creator),
+        "pole-nv" => task_run(CartPole::single(false), size, creator),
+        "double-pole" => task_run(CartPole::double(true), size, creator),
+        "double-pole-nv" => task_run(CartPole::double(false), size, creator),
+        "sine" => task_run(Regression::sine(), size, creator),
+        task => {
+            println!("Unknown task {}\n{}", task, USAGE);
+            process::exit(1);
+        }
+    };
+
+    interrupt::install();
+    let mut controller = RunController::new(options.criteria.clone());
+    loop {
+        let stats = run.generation();
+        println!("gen {:5}  best {:12.4}  mean {:12.4}  species {:3}  genes {:6.2}  hidden {:6.2}",
+                 stats.generation, stats.max_fitness, stats.mean_fitness, stats.species.len(),
+                 stats.mean_genes, stats.mean_hidden_nodes);
+
+        if let Some(reason) = controller.check(&stats) {
+            println!("Stopped after {} generations: {}", controller.generations(), reason);
+            break;
+        }
+        if interrupt::interrupted() {
+            println!("Interrupted, saving");
+            break;
+        }
+    }
+
+    if let Err(e) = checkpoint::save(&options.checkpoint, &run.pool(), run.hall_of_fame()) {
+        println!("Couldn't save checkpoint to {}: {}", options.checkpoint, e);
+    }
+
+    match run.champion() {
+        Some(champion) => {
+            let saved = File::create(&options.output)
+                .and_then(|mut file| champion.write(&mut file));
+            match saved {
+                Ok(_) => println!("Champion with fitness {} saved to {}", champion.fitness, options.output),
+                Err(e) => println!("Couldn't save champion to {}: {}", options.output, e)
+            }
+        },
+        None => println!("No champion to save")
+    }
+}
@@ -16,8 +16,82 @@ extern crate time;
 
 // Simulator
 extern crate rise;
-use rise::World;
-use rise::Actor;
+use rise::{Actor, World, SPG};
+use rise::config::{Replacement, WorldConfig};
+use rise::entities::{Entity, Kind, Shape};
+use rise::neat::Creator;
+use rise::neat::config::Config;
+use rise::neat::control::{Evolve, RunController, StopReason, StoppingCriteria};
+
+use std::env;
+use std::process;
+
+const USAGE: &'static str = "Usage: viewer [options]
+    --generations N      stop evolving after this many generations
+    --target FITNESS     stop evolving once a genome reaches this fitness
+    --stagnation N       stop evolving after N generations without a better genome
+    --time-budget SECS   stop evolving once this much time has passed
+    --seconds SECS       simulated seconds per generation (10)
+    --speed FACTOR       how much faster than real time the world runs (1)
+    --steady N           replace the worst actor older than a generation every N ticks instead of
+                         replacing everyone at once
+    --headless           no window, run as fast as possible and print each generation
+    --seed N             seed for the random number generator";
+
+struct Options {
+    criteria: StoppingCriteria,
+    generation_seconds: f64,
+    speed: f64,
+    steady: Option<u64>,
+    headless: bool,
+    seed: Option<usize>
+}
+
+impl Options {
+    fn parse() -> Result<Options, String> {
+        let mut options = Options {
+            criteria: StoppingCriteria::new(),
+            generation_seconds: SPG,
+            speed: 1f64,
+            steady: None,
+            headless: false,
+            seed: None
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(flag) = args.next() {
+            if flag == "--headless" {
+                options.headless = true;
+                continue;
+            }
+
+            let value = try!(args.next().ok_or(format!("Missing value for {}", flag)));
+            if try!(options.criteria.parse_flag(&flag, &value)) {
+                continue;
+            }
+
+            let bad_value = format!("Bad value for {}: {}", flag, value);
+            match flag.as_ref() {
+                "--seconds" => options.generation_seconds = try!(value.parse().map_err(|_| bad_value)),
+                "--speed" => options.speed = try!(value.parse().map_err(|_| bad_value)),
+                "--steady" => options.steady = Some(try!(value.parse().map_err(|_| bad_value))),
+                "--seed" => options.seed = Some(try!(value.parse().map_err(|_| bad_value))),
+                _ => return Err(format!("Unknown option {}", flag))
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+// Print why the run stopped and how good the champion got
+fn report(world: &World, controller: &RunController, reason: StopReason) {
+    match world.creator().hall_of_fame.best() {
+        Some(champion) => println!("Stopped after {} generations: {}, champion fitness {}",
+                                   controller.generations(), reason, champion.fitness),
+        None => println!("Stopped after {} generations: {}", controller.generations(), reason)
+    }
+}
 
 trait Draw {
     fn render(&self, gl: &mut GlGraphics, args: &RenderArgs) -> ();
@@ -26,14 +100,44 @@ trait Draw {
 impl Draw for Actor {
     fn render(&self, gl: &mut GlGraphics, args: &RenderArgs) {
         use graphics::*;
-        let shape = rectangle::square(0.0, 0.0, 10.0);
+        let shape = rectangle::square(-5.0, -5.0, 10.0);
         const RED:   [f32; 4] = [1.0, 0.0, 0.0, 1.0];
-        let rotation = 0.0;
+        const DEAD:  [f32; 4] = [0.3, 0.0, 0.0, 1.0];
+        const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
         gl.draw(args.viewport(), |c, gl| {
             let transform = c.transform.trans(self.position.x, self.position.y)
-                                       .rot_rad(rotation)
-                                       .trans(-25.0, -25.0);
-            ellipse(RED, shape, transform, gl)
+                                       .rot_rad(self.heading);
+            ellipse(if self.alive { RED } else { DEAD }, shape, transform, gl);
+            // Which way it's facing
+            line(WHITE, 1.0, [0.0, 0.0, 8.0, 0.0], transform, gl)
+        })
+    }
+}
+
+impl Draw for Entity {
+    fn render(&self, gl: &mut GlGraphics, args: &RenderArgs) {
+        use graphics::*;
+        const GREY:  [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+        const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+        const BLUE:  [f32; 4] = [0.0, 0.0, 1.0, 0.3];
+
+        // Eaten food isn't there
+        if !self.present() {
+            return;
+        }
+
+        let (x, y, r) = (self.position.x, self.position.y, self.radius);
+        let circle = [x - r, y - r, r * 2.0, r * 2.0];
+        gl.draw(args.viewport(), |c, gl| {
+            match self.kind {
+                Kind::Obstacle(Shape::Rectangle { half_width, half_height }) => {
+                    let rect = [x - half_width, y - half_height, half_width * 2.0, half_height * 2.0];
+                    rectangle(GREY, rect, c.transform, gl)
+                },
+                Kind::Obstacle(Shape::Circle) => ellipse(GREY, circle, c.transform, gl),
+                Kind::Food { .. } => ellipse(GREEN, circle, c.transform, gl),
+                Kind::Goal => ellipse(BLUE, circle, c.transform, gl)
+            }
         })
     }
 }
@@ -47,6 +151,10 @@ impl Draw for World {
             clear(BLACK, gl);
         });
 
+        for entity in &self.entities {
+            entity.render(&mut gl, args);
+        }
+
         for actor in &self.actors {
             actor.render(&mut gl, args);
         }
@@ -56,13 +164,47 @@ impl Draw for World {
 fn main() {
     let _ = env_logger::init();
 
+    let options = match Options::parse() {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}\n{}", e, USAGE);
+            process::exit(1);
+        }
+    };
+    let mut controller = RunController::new(options.criteria.clone());
+
+    let mut config = WorldConfig::new();
+    config.generation_seconds = options.generation_seconds;
+    if let Some(interval) = options.steady {
+        config.replacement = Replacement::SteadyState {
+            interval: interval,
+            min_age: config.ticks_per_generation()
+        };
+    }
+    let mut neat_config = Config::new();
+    neat_config.seed = options.seed;
+    let mut world = World::with_config(config, Creator::with_config(neat_config));
+
+    // Same generations as in the window, just without waiting for real time to pass
+    if options.headless {
+        loop {
+            let stats = world.generation();
+            println!("gen {:5}  best {:12.4}  mean {:12.4}  species {:3}",
+                     stats.generation, stats.max_fitness, stats.mean_fitness, stats.species.len());
+            if let Some(reason) = controller.check(&stats) {
+                report(&world, &controller, reason);
+                return;
+            }
+        }
+    }
+
     // Change this to OpenGL::V2_1 if not working.
     let opengl = OpenGL::V3_2;
 
     // Create a Glutin window.
     let mut window: Window = WindowSettings::new(
             "RISE",
-            [640, 480]
+            [world.config.width as u32, world.config.height as u32]
         )
         .opengl(opengl)
         .exit_on_esc(true)
@@ -70,7 +212,7 @@ fn main() {
         .unwrap();
     let mut gl: GlGraphics = GlGraphics::new(opengl);
 
-    let mut world = World::new();
+    let mut stopped = false;
 
     let mut events = window.events();
     while let Some(e) = events.next(&mut window) {
@@ -78,8 +220,17 @@ fn main() {
             world.render(&mut gl, &r);
         }
 
-        if let Some(_u) = e.update_args() {
-            world.update();
+        // Once stopped the window stays open on the last state
+        if let Some(u) = e.update_args() {
+            if !stopped {
+                for stats in world.step(u.dt * options.speed) {
+                    if let Some(reason) = controller.check(&stats) {
+                        report(&world, &controller, reason);
+                        stopped = true;
+                        break;
+                    }
+                }
+            }
         }
     }
 }
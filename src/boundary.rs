@@ -0,0 +1,143 @@
+// What happens at the edges of the world
+
+use Actor;
+use config::WorldConfig;
+
+use na::{Pnt2, Vec2};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Boundary {
+    // Solid walls that reflect the velocity of actors running into them
+    Bounce,
+    // Solid walls that stop actors moving further into them
+    Stop,
+    // Leaving one side brings actors back on the other. Histories jump across the world when
+    // that happens.
+    Wrap,
+    // Actors can go anywhere
+    Unbounded
+}
+
+impl Boundary {
+    pub fn walled(&self) -> bool {
+        match *self {
+            Boundary::Bounce | Boundary::Stop => true,
+            Boundary::Wrap | Boundary::Unbounded => false
+        }
+    }
+}
+
+// Keep the actor in the world, `radius` is how far it sticks out from its position
+pub fn confine(actor: &mut Actor, radius: f64, config: &WorldConfig) {
+    let bounce = config.boundary == Boundary::Bounce;
+    let (position, velocity) = keep(actor.position, actor.velocity, radius, bounce, config);
+    actor.position = position;
+    actor.velocity = velocity;
+}
+
+// Position and velocity of anything moving after the edges of the world have had their say,
+// `bounce` picks between bouncing and stopping at walls
+pub fn keep(position: Pnt2<f64>, velocity: Vec2<f64>, radius: f64, bounce: bool, config: &WorldConfig)
+    -> (Pnt2<f64>, Vec2<f64>) {
+    match config.boundary {
+        Boundary::Bounce | Boundary::Stop => {
+            let (x, vx) = wall(position.x, velocity.x, radius, config.width, bounce);
+            let (y, vy) = wall(position.y, velocity.y, radius, config.height, bounce);
+            (Pnt2::new(x, y), Vec2::new(vx, vy))
+        },
+        Boundary::Wrap => (Pnt2::new(wrap(position.x, config.width), wrap(position.y, config.height)), velocity),
+        Boundary::Unbounded => (position, velocity)
+    }
+}
+
+// Shortest way from one point to another, across the edges when the world wraps around
+pub fn offset(from: Pnt2<f64>, to: Pnt2<f64>, config: &WorldConfig) -> Vec2<f64> {
+    let offset = to - from;
+    match config.boundary {
+        Boundary::Wrap => Vec2::new(shortest(offset.x, config.width), shortest(offset.y, config.height)),
+        _ => offset
+    }
+}
+
+pub fn distance(from: Pnt2<f64>, to: Pnt2<f64>, config: &WorldConfig) -> f64 {
+    let offset = offset(from, to, config);
+    (offset.x * offset.x + offset.y * offset.y).sqrt()
+}
+
+// Position and velocity along one axis after hitting the walls at 0 and `size`
+fn wall(p: f64, v: f64, radius: f64, size: f64, bounce: bool) -> (f64, f64) {
+    let (low, high) = (radius, size - radius);
+    let stopped = if bounce { -v } else { 0f64 };
+    if p < low {
+        (low, if v < 0f64 { stopped } else { v })
+    } else if p > high {
+        (high, if v > 0f64 { stopped } else { v })
+    } else {
+        (p, v)
+    }
+}
+
+fn wrap(p: f64, size: f64) -> f64 {
+    let wrapped = p % size;
+    if wrapped < 0f64 { wrapped + size } else { wrapped }
+}
+
+fn shortest(d: f64, size: f64) -> f64 {
+    let d = wrap(d, size);
+    if d > size / 2f64 { d - size } else { d }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Actor;
+    use config::WorldConfig;
+    use neat::genetics::Genome;
+
+    use na::{Pnt2, Vec2};
+
+    fn actor(x: f64, y: f64) -> Actor {
+        let mut actor = Actor::new(Genome::new(Vec::new(), 7, 2), Pnt2::new(x, y));
+        actor.velocity = Vec2::new(-2f64, 1f64);
+        actor
+    }
+
+    #[test]
+    fn walls_bounce_or_stop_and_wrap_brings_actors_round() {
+        let mut config = WorldConfig::new();
+        config.width = 100f64;
+        config.height = 100f64;
+
+        config.boundary = Boundary::Bounce;
+        let mut bounced = actor(-3f64, 50f64);
+        confine(&mut bounced, 5f64, &config);
+        assert!(bounced.position == Pnt2::new(5f64, 50f64));
+        assert!(bounced.velocity == Vec2::new(2f64, 1f64));
+
+        config.boundary = Boundary::Stop;
+        let mut stopped = actor(50f64, 98f64);
+        confine(&mut stopped, 5f64, &config);
+        assert!(stopped.position == Pnt2::new(50f64, 95f64));
+        assert!(stopped.velocity == Vec2::new(-2f64, 0f64));
+
+        config.boundary = Boundary::Wrap;
+        let mut wrapped = actor(-3f64, 150f64);
+        confine(&mut wrapped, 5f64, &config);
+        assert!(wrapped.position == Pnt2::new(97f64, 50f64));
+        assert!(wrapped.velocity == Vec2::new(-2f64, 1f64));
+    }
+
+    #[test]
+    fn offsets_take_the_short_way_round_when_wrapping() {
+        let mut config = WorldConfig::new();
+        config.width = 100f64;
+        config.height = 100f64;
+        let (a, b) = (Pnt2::new(5f64, 50f64), Pnt2::new(95f64, 40f64));
+
+        config.boundary = Boundary::Stop;
+        assert!(offset(a, b, &config) == Vec2::new(90f64, -10f64));
+        config.boundary = Boundary::Wrap;
+        assert!(offset(a, b, &config) == Vec2::new(-10f64, -10f64));
+        assert!(distance(b, a, &config) == 200f64.sqrt());
+    }
+}
@@ -0,0 +1,267 @@
+// Predators and prey evolved against each other in one world. Each side is a team with its own
+// Creator and fitness comes from encounters: predators score a point per prey caught, caught prey
+// die so prey score the seconds they stay free. A few hall of fame members of each side join
+// every generation as veterans, so neither side can forget how to deal with strategies it has
+// beaten before. Hall of fame candidates are judged in a bout against a fixed set of opponents,
+// the other side's first generation, so their scores compare across generations. Bouts are
+// simulated on top of the world: every generation each side judges its best few genomes, each
+// in a bout of `bout_seconds` with `OPPONENTS` rivals.
+
+use boundary;
+use config::{Replacement, Spawn, WorldConfig};
+use entities::Event;
+use fitness::{Fitness, State, TimeSurvived};
+use neat::Creator;
+use neat::config::Config;
+use neat::genetics::Genome;
+use {Actor, Team, World};
+
+use na::Pnt2;
+
+pub const PREDATORS: usize = 0;
+pub const PREY: usize = 1;
+// Offset to the nearest rival
+pub const RIVAL_INPUTS: u64 = 2;
+// Predators this close to a prey catch it
+pub const CATCH_RADIUS: f64 = 15f64;
+// Hall of fame members added to each side every generation
+const VETERANS: usize = 3;
+// Opponents hall of fame candidates are judged against
+const OPPONENTS: usize = 5;
+
+// A point per prey caught
+pub struct Catches;
+
+impl Fitness for Catches {
+    fn score_lifetime(&self, history: &[State], _: f64, _: &WorldConfig) -> f64 {
+        history.iter().flat_map(|state| state.events.iter())
+            .filter(|event| match **event { Event::Caught(_) => true, _ => false })
+            .count() as f64
+    }
+}
+
+// Predators start in a column a quarter of the way across the world, prey three quarters.
+// `config.population` is the size of each side, its spawn strategy isn't used. Shorter bouts judge
+// hall of fame candidates more cheaply but on less of their behavior.
+pub fn world(config: WorldConfig, predators: Creator, prey: Creator, bout_seconds: f64) -> World {
+    let teams = teams(&config, predators, prey, VETERANS);
+    let mut world = World::with_teams(config, teams);
+
+    for &(team, rival) in [(PREDATORS, PREY), (PREY, PREDATORS)].iter() {
+        let config = bout_config(&world.config, bout_seconds);
+        let opponents: Vec<Genome> = world.team_genomes(rival).into_iter().take(OPPONENTS)
+            .map(|genome| genome.clone())
+            .collect();
+        world.teams[team].creator.set_judge(Box::new(move |genome: &Genome|
+            bout(&config, team, genome, &opponents)));
+    }
+    world
+}
+
+fn teams(config: &WorldConfig, predators: Creator, prey: Creator, veterans: usize) -> Vec<Team> {
+    let mut predators = Team::new(predators, Box::new(Catches));
+    predators.spawn = Some(column(config, config.width / 4f64, config.population + veterans));
+    predators.rival = Some(PREY);
+    predators.catches = true;
+    predators.veterans = veterans;
+
+    let mut prey = Team::new(prey, Box::new(TimeSurvived));
+    prey.spawn = Some(column(config, config.width * 3f64 / 4f64, config.population + veterans));
+    prey.rival = Some(PREDATORS);
+    prey.veterans = veterans;
+
+    vec![predators, prey]
+}
+
+// `count` points spread evenly down the world at `x`
+fn column(config: &WorldConfig, x: f64, count: usize) -> Spawn {
+    Spawn::Points((0..count).map(|i|
+        Pnt2::new(x, config.height * (i as f64 + 0.5f64) / count as f64)
+    ).collect())
+}
+
+// World of a bout lasting `seconds`
+fn bout_config(config: &WorldConfig, seconds: f64) -> WorldConfig {
+    let mut config = config.clone();
+    config.generation_seconds = seconds;
+    config
+}
+
+// Score of `genome` playing for `team` for a generation of `config` against `opponents`, the same
+// every time
+fn bout(config: &WorldConfig, team: usize, genome: &Genome, opponents: &[Genome]) -> f64 {
+    let mut config = config.clone();
+    config.population = opponents.len();
+    config.replacement = Replacement::Generational;
+    let creator = || {
+        let mut neat_config = Config::new();
+        neat_config.seed = Some(0);
+        Creator::with_config(neat_config)
+    };
+    let teams = teams(&config, creator(), creator(), 0);
+    let mut world = World::with_teams(config, teams);
+
+    let mut genomes = vec![Vec::new(), Vec::new()];
+    genomes[team] = vec![genome.clone()];
+    genomes[1 - team] = opponents.iter().map(|genome| genome.clone()).collect();
+    world.spawn(genomes);
+    for _ in 0..world.config.ticks_per_generation() {
+        world.tick();
+    }
+    world.score_lifetimes();
+    world.team_genomes(team)[0].fitness
+}
+
+// Offset to the nearest of the rivals relative to the actor's heading, ahead then to the right,
+// scaled by the size of the world
+pub fn sense_rival(actor: &Actor, rivals: &[Pnt2<f64>], config: &WorldConfig) -> Vec<f64> {
+    let distance = |rival: &Pnt2<f64>| boundary::distance(actor.position, *rival, config);
+    let nearest = rivals.iter().min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap());
+    match nearest {
+        Some(rival) => {
+            let offset = boundary::offset(actor.position, *rival, config);
+            let (sin, cos) = actor.heading.sin_cos();
+            let ahead = offset.x * cos + offset.y * sin;
+            let right = offset.y * cos - offset.x * sin;
+            vec![ahead / config.width, right / config.height]
+        },
+        None => vec![0f64, 0f64]
+    }
+}
+
+// Prey still alive that are touched by one of the catchers die. `living` are the actors that
+// moved this tick and `events` what happened to each of them, both sides get an event.
+pub fn catch(actors: &mut [Actor], living: &[usize], catchers: usize, prey: usize,
+             events: &mut [Vec<Event>], config: &WorldConfig) {
+    for (k, &j) in living.iter().enumerate() {
+        if actors[j].team != prey || !actors[j].alive {
+            continue;
+        }
+
+        let catcher = living.iter().position(|&i| {
+            let predator = &actors[i];
+            predator.team == catchers && predator.alive &&
+                boundary::distance(predator.position, actors[j].position, config) < CATCH_RADIUS
+        });
+        if let Some(c) = catcher {
+            actors[j].alive = false;
+            events[c].push(Event::Caught(j));
+            events[k].push(Event::CaughtBy(living[c]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::{Replacement, WorldConfig};
+    use energy::Energy;
+    use entities::Event;
+    use neat::Creator;
+    use neat::config::Config;
+    use neat::control::Evolve;
+    use neat::genetics::Genome;
+
+    use SPT;
+
+    use std::f64::consts::PI;
+
+    const INPUTS: u64 = 9;
+
+    fn seeded_creator() -> Creator {
+        let mut config = Config::new();
+        config.seed = Some(1);
+        Creator::with_config(config)
+    }
+
+    fn config(size: usize) -> WorldConfig {
+        let mut config = WorldConfig::new();
+        config.population = size;
+        config
+    }
+
+    fn veterans(world: &World, team: usize) -> usize {
+        world.actors.iter().filter(|actor| actor.team == team && actor.veteran).count()
+    }
+
+    #[test]
+    fn prey_next_to_predators_get_caught() {
+        let mut arena = world(config(2), seeded_creator(), seeded_creator(), 1f64);
+        let predators: Vec<Pnt2<f64>> = arena.actors.iter()
+            .filter(|actor| actor.team == PREDATORS)
+            .map(|actor| actor.position)
+            .collect();
+        for (prey, &position) in arena.actors.iter_mut().filter(|actor| actor.team == PREY).zip(predators.iter()) {
+            prey.position = position;
+        }
+        arena.update();
+        arena.score_lifetimes();
+
+        for actor in arena.actors.iter() {
+            let caught = actor.history[0].events.iter()
+                .any(|event| match *event { Event::CaughtBy(_) => true, _ => false });
+            assert!(caught == (actor.team == PREY));
+            assert!(actor.alive == (actor.team == PREDATORS));
+        }
+        let catches = arena.team_genomes(PREDATORS).iter().fold(0f64, |sum, genome| sum + genome.fitness);
+        assert!(catches == 2f64);
+        assert!(arena.team_genomes(PREY).iter().all(|genome| genome.fitness == SPT));
+    }
+
+    #[test]
+    fn rivals_are_sensed_relative_to_heading() {
+        let mut config = config(1);
+        config.width = 100f64;
+        config.height = 100f64;
+        let mut actor = Actor::new(Genome::new(Vec::new(), INPUTS, 2), Pnt2::new(50f64, 50f64));
+        let rivals = [Pnt2::new(50f64, 60f64), Pnt2::new(10f64, 10f64)];
+
+        // Facing right the nearest rival is off to the right, facing down it's straight ahead
+        let reading = sense_rival(&actor, &rivals, &config);
+        assert!(reading[0].abs() < 1e-9 && reading[1] == 0.1);
+        actor.heading = PI / 2f64;
+        let reading = sense_rival(&actor, &rivals, &config);
+        assert!((reading[0] - 0.1).abs() < 1e-9 && reading[1].abs() < 1e-9);
+    }
+
+    #[test]
+    fn veterans_join_later_generations() {
+        let mut arena = world(config(10), seeded_creator(), seeded_creator(), 2f64);
+        assert!(veterans(&arena, PREDATORS) == 0);
+        let opponents: Vec<Genome> = arena.team_genomes(PREY).into_iter().take(OPPONENTS).map(|genome| genome.clone()).collect();
+
+        arena.generation();
+
+        assert!(arena.team_genomes(PREDATORS).len() == 10);
+        assert!(arena.team_genomes(PREY).len() == 10);
+        assert!(veterans(&arena, PREDATORS) > 0);
+        assert!(veterans(&arena, PREY) > 0);
+
+        // Hall of fame scores are from bouts against the first prey, not the generation's
+        let hall_of_fame = &arena.teams[PREDATORS].creator.hall_of_fame;
+        assert!(hall_of_fame.len() > 0);
+        for entry in hall_of_fame.entries() {
+            let config = bout_config(&arena.config, 2f64);
+            assert!(entry.fitness == bout(&config, PREDATORS, &entry.genome, &opponents));
+        }
+    }
+
+    #[test]
+    fn teams_get_energy_and_steady_state_replacement() {
+        let mut config = config(10);
+        config.generation_seconds = 1f64;
+        config.replacement = Replacement::SteadyState { interval: 5, min_age: 10 };
+        let mut energy = Energy::new();
+        energy.initial = 0.5f64;
+        config.energy = Some(energy);
+        let mut arena = world(config, seeded_creator(), seeded_creator(), 1f64);
+
+        arena.generation();
+        for team in arena.teams.iter() {
+            assert!(team.creator.generation() == 1);
+        }
+        // Half a second of life was all the energy they had
+        assert!(arena.actors.iter().all(|actor| !actor.alive || actor.age <= 13));
+        assert!(arena.team_genomes(PREY).len() == 10);
+    }
+}
@@ -0,0 +1,162 @@
+use {ACTOR_INPUTS, SPG, SPT};
+use boundary::Boundary;
+use energy::Energy;
+use entities::Entity;
+use physics::Physics;
+use sensors::Sensor;
+
+use na::Pnt2;
+
+use rand::{Rng, StdRng};
+
+const WIDTH: f64 = 640f64;
+const HEIGHT: f64 = 480f64;
+const POPULATION: usize = 100;
+
+// Where actors start each generation
+#[derive(Clone, Debug)]
+pub enum Spawn {
+    // Everyone at the same point
+    Fixed(Pnt2<f64>),
+    // Anywhere in the world
+    Uniform,
+    // Actors take the points in turn, starting over when there are more actors than points
+    Points(Vec<Pnt2<f64>>)
+}
+
+impl Spawn {
+    // Starting position of the `index`th actor in a world set up by `config`
+    pub fn point(&self, index: usize, config: &WorldConfig, rng: &mut StdRng) -> Pnt2<f64> {
+        match *self {
+            Spawn::Fixed(point) => point,
+            Spawn::Uniform => Pnt2::new(rng.gen_range(0f64, config.width), rng.gen_range(0f64, config.height)),
+            Spawn::Points(ref points) if points.len() > 0 => points[index % points.len()],
+            Spawn::Points(_) => config.center()
+        }
+    }
+}
+
+// How an actor's two network outputs move it. Outputs are in -1..1.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Control {
+    // Jerk along x and y, the heading follows the velocity
+    Force,
+    // Speeds of the left and right wheels, forwards or backwards up to `max_speed`
+    DifferentialDrive { wheel_base: f64, max_speed: f64 },
+    // Force along the heading up to `max_thrust`, only forwards, and turning either way up to
+    // `max_turn` radians per second
+    ThrustTurn { max_thrust: f64, max_turn: f64 }
+}
+
+// How a world's population evolves
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Replacement {
+    // Everyone is replaced by the next generation at once
+    Generational,
+    // rtNEAT: every `interval` ticks the worst actor at least `min_age` ticks old is replaced by a
+    // new one and the world carries on. Actors are judged on their latest `min_age` ticks, so
+    // veterans don't win just by having lived longer.
+    SteadyState { interval: u64, min_age: u64 }
+}
+
+#[derive(Clone, Debug)]
+pub struct WorldConfig {
+    pub width: f64,
+    pub height: f64,
+    pub population: usize,
+    // Simulated time each generation lives for, with steady state replacement how often stats are
+    // reported
+    pub generation_seconds: f64,
+    pub replacement: Replacement,
+    pub spawn: Spawn,
+    pub boundary: Boundary,
+    pub control: Control,
+    pub physics: Physics,
+    // Actors live forever within a generation without it
+    pub energy: Option<Energy>,
+    // Obstacles, food and goals every generation starts with
+    pub entities: Vec<Entity>,
+    // Each sensor adds an input to the actors' networks
+    pub sensors: Vec<Sensor>
+}
+
+impl WorldConfig {
+    // Empty walled in 640x480 world with force controlled actors starting in the middle and no
+    // sensors
+    pub fn new() -> WorldConfig {
+        WorldConfig {
+            width: WIDTH,
+            height: HEIGHT,
+            population: POPULATION,
+            generation_seconds: SPG,
+            replacement: Replacement::Generational,
+            spawn: Spawn::Fixed(Pnt2::new(WIDTH / 2f64, HEIGHT / 2f64)),
+            boundary: Boundary::Stop,
+            control: Control::Force,
+            physics: Physics::new(),
+            energy: None,
+            entities: Vec::new(),
+            sensors: Vec::new()
+        }
+    }
+
+    // At least one
+    pub fn ticks_per_generation(&self) -> u64 {
+        ((self.generation_seconds / SPT).round() as u64).max(1)
+    }
+
+    // Latest ticks of an actor's life its fitness is scored on, the whole life when None
+    pub fn evaluation_window(&self) -> Option<usize> {
+        match self.replacement {
+            Replacement::Generational => None,
+            Replacement::SteadyState { min_age, .. } => Some(min_age.max(1) as usize)
+        }
+    }
+
+    // Number of network inputs actors need for their own state and the sensors
+    pub fn num_inputs(&self) -> u64 {
+        ACTOR_INPUTS + self.sensors.len() as u64
+    }
+
+    pub fn center(&self) -> Pnt2<f64> {
+        Pnt2::new(self.width / 2f64, self.height / 2f64)
+    }
+
+    // Starting position of the `index`th actor
+    pub fn spawn_point(&self, index: usize, rng: &mut StdRng) -> Pnt2<f64> {
+        self.spawn.point(index, self, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat;
+
+    use na::Pnt2;
+
+    #[test]
+    fn spawn_points_stay_in_world() {
+        let mut config = WorldConfig::new();
+        config.width = 100f64;
+        config.height = 50f64;
+        config.spawn = Spawn::Uniform;
+
+        let mut rng = neat::seeded_rng(1);
+        for i in 0..100 {
+            let point = config.spawn_point(i, &mut rng);
+            assert!(point.x >= 0f64 && point.x < 100f64);
+            assert!(point.y >= 0f64 && point.y < 50f64);
+        }
+    }
+
+    #[test]
+    fn spawn_points_are_taken_in_turn() {
+        let mut config = WorldConfig::new();
+        config.spawn = Spawn::Points(vec![Pnt2::new(1f64, 2f64), Pnt2::new(3f64, 4f64)]);
+
+        let mut rng = neat::seeded_rng(1);
+        assert!(config.spawn_point(0, &mut rng) == Pnt2::new(1f64, 2f64));
+        assert!(config.spawn_point(3, &mut rng) == Pnt2::new(3f64, 4f64));
+    }
+}
@@ -0,0 +1,76 @@
+// Actors paying for staying alive and moving, and eating to keep going
+
+use Actor;
+use SPT;
+use entities::Event;
+
+#[derive(Clone, Debug)]
+pub struct Energy {
+    // What actors start each generation with
+    pub initial: f64,
+    // Eating doesn't take actors past this
+    pub max: f64,
+    // Spent every second just for being alive
+    pub metabolism: f64,
+    // Spent every second per unit of force the actor puts out itself
+    pub thrust_cost: f64,
+    // Gained for each piece of food eaten
+    pub food: f64
+}
+
+impl Energy {
+    pub fn new() -> Energy {
+        Energy {
+            initial: 100f64,
+            max: 200f64,
+            metabolism: 1f64,
+            thrust_cost: 0.05f64,
+            food: 50f64
+        }
+    }
+}
+
+// Charge the actor for the last tick and feed it what it ate, returns the energy gathered. Actors
+// that run out die.
+pub fn metabolise(actor: &mut Actor, energy: &Energy, events: &[Event]) -> f64 {
+    let eaten = events.iter().filter(|event| match **event { Event::Ate(_) => true, _ => false }).count();
+    let gathered = eaten as f64 * energy.food;
+
+    let thrust = (actor.thrust.x * actor.thrust.x + actor.thrust.y * actor.thrust.y).sqrt();
+    let spent = (energy.metabolism + thrust * energy.thrust_cost) * SPT;
+
+    actor.energy = (actor.energy - spent + gathered).min(energy.max);
+    if actor.energy <= 0f64 {
+        actor.energy = 0f64;
+        actor.alive = false;
+    }
+    gathered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Actor, SPT};
+    use entities::Event;
+    use neat::genetics::Genome;
+
+    use na::{Pnt2, Vec2};
+
+    #[test]
+    fn thrust_costs_and_food_restores_energy() {
+        let energy = Energy::new();
+        let mut actor = Actor::new(Genome::new(Vec::new(), 7, 2), Pnt2::new(0f64, 0f64));
+        actor.energy = 10f64;
+        actor.thrust = Vec2::new(60f64, 80f64);
+
+        assert!(metabolise(&mut actor, &energy, &[]) == 0f64);
+        assert!((actor.energy - (10f64 - 6f64 * SPT)).abs() < 1e-9);
+
+        assert!(metabolise(&mut actor, &energy, &[Event::Ate(0), Event::InGoal(1)]) == 50f64);
+        assert!(actor.energy > 50f64 && actor.alive);
+
+        actor.energy = SPT;
+        metabolise(&mut actor, &energy, &[]);
+        assert!(actor.energy == 0f64 && !actor.alive);
+    }
+}
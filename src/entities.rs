@@ -0,0 +1,291 @@
+// Things in the world besides actors: obstacles to bump into, food to eat and goal zones to reach.
+// Any of them can be given a velocity to make them move.
+
+use {Actor, SPT};
+use boundary;
+use config::WorldConfig;
+
+use na::{Pnt2, Vec2};
+
+use rand::{Rng, StdRng};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shape {
+    Circle,
+    // Axis aligned, centered on the entity's position
+    Rectangle { half_width: f64, half_height: f64 }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Kind {
+    // Actors can't move through obstacles
+    Obstacle(Shape),
+    // Eaten by the first actor to touch it, comes back somewhere random after `respawn` ticks
+    Food { respawn: u64 },
+    // Actors inside a goal are told so every tick
+    Goal
+}
+
+// What happened to an actor during a tick, the usize is the entity's index in the world
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Event {
+    Collided(usize),
+    Ate(usize),
+    InGoal(usize),
+    // Encounters with actors of a rival team, the usize is the other actor's index
+    Caught(usize),
+    CaughtBy(usize)
+}
+
+#[derive(Clone, Debug)]
+pub struct Entity {
+    pub kind: Kind,
+    pub position: Pnt2<f64>,
+    // For rectangles this is the radius of the circle around them
+    pub radius: f64,
+    // Zero for things that stay put
+    pub velocity: Vec2<f64>,
+    // Ticks until eaten food is back, 0 while it's there
+    pub respawn_in: u64
+}
+
+impl Entity {
+    fn new(kind: Kind, position: Pnt2<f64>, radius: f64) -> Entity {
+        Entity {
+            kind: kind,
+            position: position,
+            radius: radius,
+            velocity: Vec2::new(0f64, 0f64),
+            respawn_in: 0
+        }
+    }
+
+    pub fn circle(position: Pnt2<f64>, radius: f64) -> Entity {
+        Entity::new(Kind::Obstacle(Shape::Circle), position, radius)
+    }
+
+    pub fn rectangle(position: Pnt2<f64>, width: f64, height: f64) -> Entity {
+        let shape = Shape::Rectangle { half_width: width / 2f64, half_height: height / 2f64 };
+        let radius = (width * width + height * height).sqrt() / 2f64;
+        Entity::new(Kind::Obstacle(shape), position, radius)
+    }
+
+    pub fn food(position: Pnt2<f64>, radius: f64, respawn: u64) -> Entity {
+        Entity::new(Kind::Food { respawn: respawn }, position, radius)
+    }
+
+    pub fn goal(position: Pnt2<f64>, radius: f64) -> Entity {
+        Entity::new(Kind::Goal, position, radius)
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec2<f64>) -> Entity {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn present(&self) -> bool {
+        self.respawn_in == 0
+    }
+}
+
+// Resolve collisions between the actor and the entities, returns what happened to the actor
+pub fn interact(actor: &mut Actor, radius: f64, entities: &mut [Entity], config: &WorldConfig) -> Vec<Event> {
+    let mut events = Vec::new();
+
+    for (i, entity) in entities.iter_mut().enumerate() {
+        if !entity.present() {
+            continue;
+        }
+
+        // Where the entity is seen from the actor, which can be across the edge of the world
+        let position = actor.position + boundary::offset(actor.position, entity.position, config);
+        match entity.kind {
+            Kind::Obstacle(shape) => {
+                if collide(actor, radius, position, entity.radius, shape, entity.velocity) {
+                    events.push(Event::Collided(i));
+                }
+            },
+            Kind::Food { respawn } => {
+                if distance(actor.position, position) < radius + entity.radius {
+                    // Respawning takes at least a tick so food can't be eaten twice in one
+                    entity.respawn_in = respawn.max(1);
+                    events.push(Event::Ate(i));
+                }
+            },
+            Kind::Goal => {
+                if distance(actor.position, position) < entity.radius {
+                    events.push(Event::InGoal(i));
+                }
+            }
+        }
+    }
+
+    events
+}
+
+// Count down eaten food, food that's due comes back at a random spot
+pub fn respawn(entities: &mut [Entity], config: &WorldConfig, rng: &mut StdRng) {
+    for entity in entities.iter_mut() {
+        if entity.respawn_in == 0 {
+            continue;
+        }
+
+        entity.respawn_in -= 1;
+        if entity.respawn_in == 0 {
+            entity.position = Pnt2::new(rng.gen_range(0f64, config.width),
+                                        rng.gen_range(0f64, config.height));
+        }
+    }
+}
+
+// Move entities along by a tick. Walls bounce them back even where they stop actors, so they
+// can't get stuck on them, otherwise they wrap around or carry on like actors do.
+pub fn advance(entities: &mut [Entity], config: &WorldConfig) {
+    for entity in entities.iter_mut() {
+        let position = entity.position + entity.velocity * SPT;
+        let (position, velocity) = boundary::keep(position, entity.velocity, entity.radius, true, config);
+        entity.position = position;
+        entity.velocity = velocity;
+    }
+}
+
+fn distance(a: Pnt2<f64>, b: Pnt2<f64>) -> f64 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+// Push the actor out of the obstacle and stop it moving further in, true if they touched.
+// Moving obstacles carry the actor along with them.
+fn collide(actor: &mut Actor, radius: f64, center: Pnt2<f64>, obstacle_radius: f64, shape: Shape,
+           obstacle_velocity: Vec2<f64>) -> bool {
+    // Point of the obstacle's surface nearest to the actor and which way is out
+    let (surface, normal) = match shape {
+        Shape::Circle => {
+            let dist = distance(actor.position, center);
+            if dist >= radius + obstacle_radius {
+                return false;
+            }
+            let normal = if dist > 0f64 {
+                (actor.position - center) / dist
+            } else {
+                Vec2::new(1f64, 0f64)
+            };
+            (center + normal * obstacle_radius, normal)
+        },
+        Shape::Rectangle { half_width, half_height } => {
+            let closest = Pnt2::new(
+                actor.position.x.max(center.x - half_width).min(center.x + half_width),
+                actor.position.y.max(center.y - half_height).min(center.y + half_height));
+            let dist = distance(actor.position, closest);
+            if dist >= radius {
+                return false;
+            }
+
+            if dist > 0f64 {
+                (closest, (actor.position - closest) / dist)
+            } else {
+                // Center is inside, leave through the nearest side
+                let dx = actor.position.x - center.x;
+                let dy = actor.position.y - center.y;
+                if half_width - dx.abs() < half_height - dy.abs() {
+                    let side = dx.signum();
+                    (Pnt2::new(center.x + side * half_width, actor.position.y), Vec2::new(side, 0f64))
+                } else {
+                    let side = dy.signum();
+                    (Pnt2::new(actor.position.x, center.y + side * half_height), Vec2::new(0f64, side))
+                }
+            }
+        }
+    };
+
+    actor.position = surface + normal * radius;
+    let relative = actor.velocity - obstacle_velocity;
+    let into = relative.x * normal.x + relative.y * normal.y;
+    if into < 0f64 {
+        actor.velocity = actor.velocity - normal * into;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Actor, SPT};
+    use config::WorldConfig;
+    use neat;
+    use neat::genetics::Genome;
+
+    use na::{Pnt2, Vec2};
+
+    fn actor(x: f64, y: f64) -> Actor {
+        let mut actor = Actor::new(Genome::new(Vec::new(), 7, 2), Pnt2::new(x, y));
+        actor.velocity = Vec2::new(1f64, 1f64);
+        actor
+    }
+
+    #[test]
+    fn obstacles_push_actors_out() {
+        let config = WorldConfig::new();
+        let mut entities = vec![
+            Entity::circle(Pnt2::new(0f64, 0f64), 10f64),
+            Entity::rectangle(Pnt2::new(100f64, 0f64), 20f64, 20f64)
+        ];
+
+        let mut circled = actor(-12f64, 0f64);
+        assert!(interact(&mut circled, 5f64, &mut entities, &config) == vec![Event::Collided(0)]);
+        assert!(circled.position == Pnt2::new(-15f64, 0f64));
+        assert!(circled.velocity == Vec2::new(0f64, 1f64));
+
+        let mut boxed = actor(100f64, 8f64);
+        assert!(interact(&mut boxed, 5f64, &mut entities, &config) == vec![Event::Collided(1)]);
+        assert!(boxed.position == Pnt2::new(100f64, 15f64));
+        assert!(boxed.velocity == Vec2::new(1f64, 1f64));
+    }
+
+    #[test]
+    fn food_is_eaten_and_respawns() {
+        let config = WorldConfig::new();
+        let mut entities = vec![
+            Entity::food(Pnt2::new(0f64, 0f64), 2f64, 2),
+            Entity::goal(Pnt2::new(0f64, 0f64), 20f64)
+        ];
+
+        let mut hungry = actor(3f64, 0f64);
+        assert!(interact(&mut hungry, 5f64, &mut entities, &config) == vec![Event::Ate(0), Event::InGoal(1)]);
+        assert!(interact(&mut hungry, 5f64, &mut entities, &config) == vec![Event::InGoal(1)]);
+
+        let mut rng = neat::seeded_rng(1);
+        respawn(&mut entities, &config, &mut rng);
+        assert!(!entities[0].present());
+        respawn(&mut entities, &config, &mut rng);
+        assert!(entities[0].present());
+    }
+
+    #[test]
+    fn moving_obstacles_push_actors_along() {
+        let config = WorldConfig::new();
+        let mut entities = vec![
+            Entity::circle(Pnt2::new(0f64, 0f64), 10f64).with_velocity(Vec2::new(4f64, 0f64))
+        ];
+
+        let mut pushed = actor(12f64, 0f64);
+        assert!(interact(&mut pushed, 5f64, &mut entities, &config) == vec![Event::Collided(0)]);
+        assert!(pushed.position == Pnt2::new(15f64, 0f64));
+        assert!(pushed.velocity == Vec2::new(4f64, 1f64));
+    }
+
+    #[test]
+    fn moving_entities_bounce_off_edges() {
+        let mut config = WorldConfig::new();
+        config.width = 100f64;
+        config.height = 100f64;
+        let mut entities = vec![
+            Entity::circle(Pnt2::new(50f64, 50f64), 5f64).with_velocity(Vec2::new(1f64 / SPT, 0f64)),
+            Entity::goal(Pnt2::new(94f64, 50f64), 5f64).with_velocity(Vec2::new(2f64 / SPT, 0f64))
+        ];
+
+        advance(&mut entities, &config);
+        assert!(entities[0].position == Pnt2::new(51f64, 50f64));
+        assert!(entities[1].position == Pnt2::new(95f64, 50f64));
+        assert!(entities[1].velocity == Vec2::new(-2f64 / SPT, 0f64));
+    }
+}
@@ -0,0 +1,247 @@
+// Scoring actors in a World. A fitness function sees the history of an actor and can score it
+// every tick, at the end of its lifetime, or both. With steady state replacement only the latest
+// ticks are kept and judged. It's given the world's config so distances
+// can be measured the way the world's edges work.
+
+use SPT;
+use boundary;
+use config::WorldConfig;
+use entities::Event;
+
+use na::{Pnt2, Vec2};
+
+use std::collections::VecDeque;
+use std::f64;
+
+// Snapshot of an actor taken after every tick
+#[derive(Clone, Debug)]
+pub struct State {
+    pub tick: u64,
+    pub position: Pnt2<f64>,
+    pub velocity: Vec2<f64>,
+    pub acceleration: Vec2<f64>,
+    pub heading: f64,
+    // Energy left, and gained from food during the tick
+    pub energy: f64,
+    pub gathered: f64,
+    // What happened with the world's entities during the tick
+    pub events: Vec<Event>
+}
+
+// How tick scores add up over a lifetime
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Total {
+    Sum,
+    Max,
+    Mean,
+    // Only the latest score counts
+    Last
+}
+
+#[derive(Clone, Debug)]
+pub struct RunningTotal {
+    pub total: Total,
+    sum: f64,
+    max: f64,
+    last: f64,
+    count: u64,
+    // Only the latest scores count when set, they're kept in `recent`
+    window: Option<usize>,
+    recent: VecDeque<f64>
+}
+
+impl RunningTotal {
+    pub fn new(total: Total) -> RunningTotal {
+        RunningTotal {
+            total: total,
+            sum: 0f64,
+            max: 0f64,
+            last: 0f64,
+            count: 0,
+            window: None,
+            recent: VecDeque::new()
+        }
+    }
+
+    // Combines only the latest `window` scores
+    pub fn windowed(total: Total, window: Option<usize>) -> RunningTotal {
+        RunningTotal { window: window, ..RunningTotal::new(total) }
+    }
+
+    pub fn add(&mut self, score: f64) {
+        self.last = score;
+        match self.window {
+            Some(window) => {
+                self.recent.push_back(score);
+                while self.recent.len() > window {
+                    self.recent.pop_front();
+                }
+            },
+            None => {
+                self.max = if self.count == 0 { score } else { self.max.max(score) };
+                self.sum += score;
+                self.count += 1;
+            }
+        }
+    }
+
+    // Zero until a score has been added
+    pub fn value(&self) -> f64 {
+        let (sum, max, count) = match self.window {
+            Some(_) => (self.recent.iter().fold(0f64, |acc, &score| acc + score),
+                        self.recent.iter().fold(f64::NEG_INFINITY, |acc, &score| acc.max(score)),
+                        self.recent.len() as u64),
+            None => (self.sum, self.max, self.count)
+        };
+        if count == 0 {
+            return 0f64;
+        }
+
+        match self.total {
+            Total::Sum => sum,
+            Total::Max => max,
+            Total::Mean => sum / count as f64,
+            Total::Last => self.last
+        }
+    }
+}
+
+pub trait Fitness {
+    // Score of the latest tick, `history` ends with the current state. None when only the
+    // lifetime is scored.
+    fn score_tick(&self, _history: &[State], _config: &WorldConfig) -> Option<f64> {
+        None
+    }
+
+    // How tick scores are combined
+    fn total(&self) -> Total {
+        Total::Sum
+    }
+
+    // Final fitness, given the history being judged and the combined tick scores
+    fn score_lifetime(&self, _history: &[State], total: f64, _config: &WorldConfig) -> f64 {
+        total
+    }
+}
+
+// Closer to the target is better, minus the distance to it combined over the ticks
+pub struct ReachTarget {
+    pub target: Pnt2<f64>,
+    pub total: Total
+}
+
+impl Fitness for ReachTarget {
+    fn score_tick(&self, history: &[State], config: &WorldConfig) -> Option<f64> {
+        history.last().map(|state| -boundary::distance(state.position, self.target, config))
+    }
+
+    fn total(&self) -> Total {
+        self.total
+    }
+}
+
+// Length of the path the actor moved along
+pub struct DistanceTravelled;
+
+impl Fitness for DistanceTravelled {
+    fn score_lifetime(&self, history: &[State], _: f64, config: &WorldConfig) -> f64 {
+        history.windows(2).fold(0f64, |acc, pair|
+            acc + boundary::distance(pair[0].position, pair[1].position, config))
+    }
+}
+
+// Seconds the actor was alive for, actors that run out of energy stop recording history
+pub struct TimeSurvived;
+
+impl Fitness for TimeSurvived {
+    fn score_lifetime(&self, history: &[State], _: f64, _: &WorldConfig) -> f64 {
+        history.len() as f64 * SPT
+    }
+}
+
+// Energy gained from food over the lifetime
+pub struct EnergyGathered;
+
+impl Fitness for EnergyGathered {
+    fn score_lifetime(&self, history: &[State], _: f64, _: &WorldConfig) -> f64 {
+        history.iter().fold(0f64, |acc, state| acc + state.gathered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boundary::Boundary;
+    use config::WorldConfig;
+
+    use na::{Pnt2, Vec2};
+
+    fn history(points: &[(f64, f64)]) -> Vec<State> {
+        points.iter().enumerate().map(|(i, &(x, y))| State {
+            tick: i as u64,
+            position: Pnt2::new(x, y),
+            velocity: Vec2::new(0f64, 0f64),
+            acceleration: Vec2::new(0f64, 0f64),
+            heading: 0f64,
+            energy: 0f64,
+            gathered: i as f64,
+            events: Vec::new()
+        }).collect()
+    }
+
+    #[test]
+    fn running_totals_combine_scores() {
+        let mut totals: Vec<RunningTotal> = vec![Total::Sum, Total::Max, Total::Mean, Total::Last]
+            .into_iter().map(RunningTotal::new).collect();
+        for total in totals.iter_mut() {
+            for &score in [1f64, 4f64, 1f64].iter() {
+                total.add(score);
+            }
+        }
+
+        let values: Vec<f64> = totals.iter().map(|total| total.value()).collect();
+        assert!(values == vec![6f64, 4f64, 2f64, 1f64]);
+
+        // The 4 drops out of a window of two
+        let mut totals: Vec<RunningTotal> = vec![Total::Sum, Total::Max, Total::Mean, Total::Last]
+            .into_iter().map(|total| RunningTotal::windowed(total, Some(2))).collect();
+        for total in totals.iter_mut() {
+            for &score in [1f64, 4f64, 1f64, 3f64].iter() {
+                total.add(score);
+            }
+        }
+
+        let values: Vec<f64> = totals.iter().map(|total| total.value()).collect();
+        assert!(values == vec![4f64, 3f64, 2f64, 3f64]);
+    }
+
+    #[test]
+    fn built_in_fitness_functions_score_history() {
+        let history = history(&[(0f64, 0f64), (3f64, 4f64), (3f64, 0f64)]);
+        let config = WorldConfig::new();
+
+        let reach = ReachTarget { target: Pnt2::new(3f64, 0f64), total: Total::Last };
+        assert!(reach.score_tick(&history[..2], &config) == Some(-4f64));
+        assert!(DistanceTravelled.score_lifetime(&history, 0f64, &config) == 9f64);
+        assert!(TimeSurvived.score_lifetime(&history, 0f64, &config) == 3f64 * SPT);
+        assert!(EnergyGathered.score_lifetime(&history, 0f64, &config) == 3f64);
+    }
+
+    #[test]
+    fn distances_go_across_wrapped_edges() {
+        let mut config = WorldConfig::new();
+        config.width = 100f64;
+        config.height = 100f64;
+        config.boundary = Boundary::Wrap;
+        // Off the right edge and back in on the left
+        let history = history(&[(96f64, 50f64), (99f64, 50f64), (2f64, 50f64)]);
+
+        let reach = ReachTarget { target: Pnt2::new(98f64, 50f64), total: Total::Last };
+        assert!(reach.score_tick(&history, &config) == Some(-4f64));
+        assert!(DistanceTravelled.score_lifetime(&history, 0f64, &config) == 6f64);
+
+        // The long way round without wrapping
+        config.boundary = Boundary::Bounce;
+        assert!(DistanceTravelled.score_lifetime(&history, 0f64, &config) == 100f64);
+    }
+}
@@ -0,0 +1,142 @@
+use neat::benchmarks::Benchmark;
+use neat::neurology::Network;
+use neat::task::{Evaluation, Task};
+
+use std::f64::consts::PI;
+
+const GRAVITY: f64 = -9.8;
+const CART_MASS: f64 = 1.0;
+const FORCE: f64 = 10.0;
+const TRACK_LIMIT: f64 = 2.4;
+const TIME_STEP: f64 = 0.01;
+const MAX_STEPS: u64 = 10000;
+
+#[derive(Copy, Clone)]
+struct Pole {
+    mass: f64,
+    half_length: f64,
+    angle: f64,
+    velocity: f64
+}
+
+// Poles hinged on a cart that the network pushes left or right, fitness is the number of steps
+// before the cart leaves the track or a pole falls too far. Without velocities the network only
+// sees positions and has to work out the rest itself.
+pub struct CartPole {
+    poles: Vec<Pole>,
+    velocities: bool,
+    failure_angle: f64,
+    max_steps: u64
+}
+
+impl CartPole {
+    pub fn single(velocities: bool) -> CartPole {
+        CartPole {
+            poles: vec![Pole { mass: 0.1, half_length: 0.5, angle: PI / 180.0, velocity: 0.0 }],
+            velocities: velocities,
+            failure_angle: 12.0 * PI / 180.0,
+            max_steps: MAX_STEPS
+        }
+    }
+
+    pub fn double(velocities: bool) -> CartPole {
+        CartPole {
+            poles: vec![
+                Pole { mass: 0.1, half_length: 0.5, angle: PI / 180.0, velocity: 0.0 },
+                Pole { mass: 0.01, half_length: 0.05, angle: 0.0, velocity: 0.0 }
+            ],
+            velocities: velocities,
+            failure_angle: 36.0 * PI / 180.0,
+            max_steps: MAX_STEPS
+        }
+    }
+
+    fn inputs(&self, x: f64, x_velocity: f64, poles: &[Pole]) -> Vec<f64> {
+        let mut inputs = vec![x / TRACK_LIMIT];
+        if self.velocities {
+            inputs.push(x_velocity);
+        }
+        for pole in poles.iter() {
+            inputs.push(pole.angle / self.failure_angle);
+            if self.velocities {
+                inputs.push(pole.velocity);
+            }
+        }
+        inputs.push(1f64);
+        inputs
+    }
+}
+
+impl Task for CartPole {
+    fn num_inputs(&self) -> u64 {
+        let per_object = if self.velocities { 2 } else { 1 };
+        (per_object * (self.poles.len() + 1) + 1) as u64
+    }
+
+    fn num_outputs(&self) -> u64 { 1 }
+
+    fn evaluate(&mut self, network: &Network) -> Evaluation {
+        let mut poles = self.poles.clone();
+        let mut x = 0f64;
+        let mut x_velocity = 0f64;
+
+        let mut steps = 0;
+        while steps < self.max_steps {
+            let force = network.evaluate(self.inputs(x, x_velocity, &poles))[0] * FORCE;
+
+            // Frictionless cart and poles, integrated with Euler
+            let mut effective_force = 0f64;
+            let mut effective_mass = 0f64;
+            for pole in poles.iter() {
+                let (sin, cos) = (pole.angle.sin(), pole.angle.cos());
+                effective_force += pole.mass * pole.half_length * pole.velocity.powi(2) * sin +
+                    0.75 * pole.mass * cos * GRAVITY * sin;
+                effective_mass += pole.mass * (1f64 - 0.75 * cos.powi(2));
+            }
+            let x_acceleration = (force + effective_force) / (CART_MASS + effective_mass);
+
+            for pole in poles.iter_mut() {
+                let acceleration = -0.75 * (x_acceleration * pole.angle.cos() +
+                                            GRAVITY * pole.angle.sin()) / pole.half_length;
+                pole.angle += pole.velocity * TIME_STEP;
+                pole.velocity += acceleration * TIME_STEP;
+            }
+            x += x_velocity * TIME_STEP;
+            x_velocity += x_acceleration * TIME_STEP;
+
+            if x.abs() > TRACK_LIMIT || poles.iter().any(|pole| pole.angle.abs() > self.failure_angle) {
+                break;
+            }
+            steps += 1;
+        }
+
+        let mut evaluation = Evaluation::new(steps as f64);
+        evaluation.metadata.insert("steps".to_string(), steps as f64);
+        evaluation
+    }
+}
+
+impl Benchmark for CartPole {
+    fn solved(&self, evaluation: &Evaluation) -> bool {
+        evaluation.fitness >= self.max_steps as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat::genetics::Genome;
+    use neat::task::Task;
+
+    #[test]
+    fn unbalanced_poles_fall() {
+        // No genes means no force, so the pole falls over on its own
+        let mut cart_pole = CartPole::double(true);
+        let genome = Genome::new(Vec::new(), cart_pole.num_inputs(), cart_pole.num_outputs());
+        let evaluation = cart_pole.evaluate(&genome.network);
+
+        assert!(cart_pole.num_inputs() == 7);
+        assert!(!cart_pole.solved(&evaluation));
+        assert!(evaluation.fitness > 0f64);
+    }
+}
@@ -0,0 +1,58 @@
+// Tasks with known solutions to check changes to the evolution code against
+
+pub mod cart_pole;
+pub mod regression;
+pub mod xor;
+
+use neat::population::Population;
+use neat::task::{Evaluation, Task};
+
+pub use self::cart_pole::CartPole;
+pub use self::regression::Regression;
+pub use self::xor::Xor;
+
+pub trait Benchmark: Task {
+    fn solved(&self, evaluation: &Evaluation) -> bool;
+}
+
+#[derive(Clone, Debug)]
+pub struct BenchmarkResult {
+    // Generation the first solution was found in, None if it never was
+    pub solved_in: Option<u64>,
+    pub fitness: f64,
+    // Size of the solution, or of the best genome if there is no solution
+    pub genes: usize,
+    pub hidden_nodes: usize
+}
+
+// Evolve the population until a genome solves the benchmark or `max_generations` have passed
+pub fn run<B: Benchmark>(benchmark: &mut B, population: &mut Population, max_generations: u64)
+        -> BenchmarkResult {
+    for _ in 0..max_generations {
+        population.step(benchmark);
+
+        let solved = population.evaluations.iter()
+            .position(|evaluation| benchmark.solved(evaluation));
+        if let Some(i) = solved {
+            let generation = population.creator.generation();
+            let solution = &population.parents[i];
+            info!("Solved in generation {}: {:?}", generation, solution);
+            return BenchmarkResult {
+                solved_in: Some(generation),
+                fitness: solution.fitness,
+                genes: solution.genes.len(),
+                hidden_nodes: solution.network.num_hidden()
+            };
+        }
+    }
+
+    match population.champion {
+        Some(ref champion) => BenchmarkResult {
+            solved_in: None,
+            fitness: champion.fitness,
+            genes: champion.genes.len(),
+            hidden_nodes: champion.network.num_hidden()
+        },
+        None => BenchmarkResult { solved_in: None, fitness: 0f64, genes: 0, hidden_nodes: 0 }
+    }
+}
@@ -0,0 +1,66 @@
+use neat::benchmarks::Benchmark;
+use neat::neurology::Network;
+use neat::task::{Evaluation, Task};
+
+use std::f64::consts::PI;
+
+const TOLERANCE: f64 = 0.01;
+
+// Curve fitting, the network gets x and a bias and should output f(x). Fitness is minus the mean
+// squared error.
+pub struct Regression {
+    samples: Vec<(f64, f64)>,
+    tolerance: f64
+}
+
+impl Regression {
+    pub fn new<F: Fn(f64) -> f64>(function: F, xs: Vec<f64>) -> Regression {
+        Regression {
+            samples: xs.into_iter().map(|x| (x, function(x))).collect(),
+            tolerance: TOLERANCE
+        }
+    }
+
+    // One period of a sine wave, squeezed into -1..1 on both axes
+    pub fn sine() -> Regression {
+        let xs = (0..21).map(|i| i as f64 / 10f64 - 1f64).collect();
+        Regression::new(|x: f64| (x * PI).sin() * 0.9, xs)
+    }
+}
+
+impl Task for Regression {
+    fn num_inputs(&self) -> u64 { 2 }
+    fn num_outputs(&self) -> u64 { 1 }
+
+    fn evaluate(&mut self, network: &Network) -> Evaluation {
+        let error = self.samples.iter().fold(0f64, |acc, &(x, y)|
+            acc + (network.evaluate(vec![x, 1f64])[0] - y).powi(2));
+        Evaluation::new(-error / self.samples.len() as f64)
+    }
+}
+
+impl Benchmark for Regression {
+    fn solved(&self, evaluation: &Evaluation) -> bool {
+        -evaluation.fitness < self.tolerance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat::genetics::{Gene, Genome};
+    use neat::task::Task;
+
+    #[test]
+    fn exact_fit_is_solved() {
+        // Output is sigmoid(x), so fitting sigmoid itself is exact
+        let genome = Genome::new(vec![
+            Gene{ into: 0, out: 2, weight: 1.0, enabled: true, innovation: 1 }
+        ], 2, 1);
+        let xs = vec![-1.0, 0.0, 1.0];
+        let mut regression = Regression::new(|x: f64| genome.network.evaluate(vec![x, 1.0])[0], xs);
+
+        let evaluation = regression.evaluate(&genome.network);
+        assert!(regression.solved(&evaluation));
+    }
+}
@@ -0,0 +1,68 @@
+use neat::benchmarks::Benchmark;
+use neat::neurology::Network;
+use neat::task::{Evaluation, Task};
+
+const CASES: [([f64; 2], f64); 4] = [
+    ([0f64, 0f64], 0f64),
+    ([0f64, 1f64], 1f64),
+    ([1f64, 0f64], 1f64),
+    ([1f64, 1f64], 0f64)
+];
+
+// Two inputs plus a bias, one output that should be above 0.5 for true. Fitness is 4 minus the
+// squared error so a perfect network scores 4.
+pub struct Xor;
+
+impl Task for Xor {
+    fn num_inputs(&self) -> u64 { 3 }
+    fn num_outputs(&self) -> u64 { 1 }
+
+    fn evaluate(&mut self, network: &Network) -> Evaluation {
+        let mut error = 0f64;
+        let mut correct = 0;
+        for &(inputs, expected) in CASES.iter() {
+            let output = network.evaluate(vec![inputs[0], inputs[1], 1f64])[0];
+            error += (output - expected).powi(2);
+            if (output > 0.5) == (expected > 0.5) {
+                correct += 1;
+            }
+        }
+
+        let mut evaluation = Evaluation::new(4f64 - error);
+        evaluation.metadata.insert("correct".to_string(), correct as f64);
+        evaluation
+    }
+}
+
+impl Benchmark for Xor {
+    fn solved(&self, evaluation: &Evaluation) -> bool {
+        evaluation.metadata.get("correct") == Some(&(CASES.len() as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat::genetics::{Gene, Genome};
+    use neat::task::Task;
+
+    #[test]
+    fn xor_recognizes_solution() {
+        // Hidden neuron 4 is OR, hidden neuron 5 is AND, output is OR and not AND
+        let genome = Genome::new(vec![
+            Gene{ into: 0, out: 4, weight: 1.0, enabled: true, innovation: 1 },
+            Gene{ into: 1, out: 4, weight: 1.0, enabled: true, innovation: 2 },
+            Gene{ into: 2, out: 4, weight: -0.5, enabled: true, innovation: 3 },
+            Gene{ into: 0, out: 5, weight: 1.0, enabled: true, innovation: 4 },
+            Gene{ into: 1, out: 5, weight: 1.0, enabled: true, innovation: 5 },
+            Gene{ into: 2, out: 5, weight: -1.5, enabled: true, innovation: 6 },
+            Gene{ into: 4, out: 3, weight: 1.0, enabled: true, innovation: 7 },
+            Gene{ into: 5, out: 3, weight: -1.0, enabled: true, innovation: 8 },
+            Gene{ into: 2, out: 3, weight: -1.0, enabled: true, innovation: 9 }
+        ], 3, 1);
+
+        let mut xor = Xor;
+        let evaluation = xor.evaluate(&genome.network);
+        assert!(xor.solved(&evaluation));
+    }
+}
@@ -0,0 +1,84 @@
+// A checkpoint is a directory with the current population and the hall of fame, both in the
+// plain text format of `Genome::write`.
+
+use neat::genetics::Genome;
+use neat::hall_of_fame::HallOfFame;
+
+use std::fs::{self, File};
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+const POPULATION_FILE: &'static str = "population.genomes";
+const HALL_OF_FAME_FILE: &'static str = "hall_of_fame.genomes";
+
+pub fn save<P: AsRef<Path>>(dir: P, population: &[&Genome], hall_of_fame: &HallOfFame)
+        -> io::Result<()> {
+    try!(fs::create_dir_all(dir.as_ref()));
+
+    let mut file = try!(File::create(dir.as_ref().join(POPULATION_FILE)));
+    for genome in population.iter() {
+        try!(genome.write(&mut file));
+    }
+
+    hall_of_fame.save(dir.as_ref().join(HALL_OF_FAME_FILE))
+}
+
+pub fn load_population<P: AsRef<Path>>(dir: P) -> io::Result<Vec<Genome>> {
+    let mut text = String::new();
+    try!(try!(File::open(dir.as_ref().join(POPULATION_FILE))).read_to_string(&mut text));
+    Genome::parse_all(&text)
+}
+
+pub fn load_hall_of_fame<P: AsRef<Path>>(dir: P, capacity: usize) -> io::Result<HallOfFame> {
+    HallOfFame::load(dir.as_ref().join(HALL_OF_FAME_FILE), capacity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat;
+    use neat::genetics::Genome;
+    use neat::hall_of_fame::HallOfFame;
+
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DIRS: AtomicUsize = AtomicUsize::new(0);
+
+    // Fresh directory for each test, so test runs don't share files
+    fn temp_dir() -> PathBuf {
+        let n = DIRS.fetch_add(1, Ordering::SeqCst);
+        env::temp_dir().join(format!("rise-checkpoint-test-{}-{}", process::id(), n))
+    }
+
+    #[test]
+    fn checkpoints_round_trip() {
+        let mut rng = neat::seeded_rng(1);
+        let genomes: Vec<Genome> = (0..3).map(|i| {
+            let mut genome = Genome::random(2, 1, &mut rng);
+            genome.fitness = i as f64;
+            genome
+        }).collect();
+        // Entries are ranked by what they were considered with, not the genome's own fitness
+        let mut hall_of_fame = HallOfFame::new(5);
+        hall_of_fame.consider(&genomes[2], 2.0, 4, 1);
+        hall_of_fame.consider(&genomes[1], 3.0, 5, 2);
+
+        let dir = temp_dir();
+        save(&dir, &genomes.iter().collect::<Vec<_>>(), &hall_of_fame).unwrap();
+
+        let population = load_population(&dir).unwrap();
+        assert!(population.len() == 3);
+        assert!(population[2].fitness == 2.0);
+
+        let loaded = load_hall_of_fame(&dir, 5).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(loaded.len() == 2);
+        assert!(loaded.best().unwrap().generation == 5 && loaded.best().unwrap().fitness == 3.0);
+        assert!(loaded.entries()[1].fitness == 2.0);
+    }
+}
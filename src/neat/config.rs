@@ -0,0 +1,151 @@
+use neat::selection::{Rank, Roulette, Selection, Tournament, Uniform};
+
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+const TOURNAMENT_SIZE: usize = 3;
+const INTERSPECIES_RATE: f64 = 0.001;
+const NOVELTY_NEIGHBOURS: usize = 15;
+const NOVELTY_THRESHOLD: f64 = 6.0;
+const HALL_OF_FAME_SIZE: usize = 10;
+
+// What genomes are selected on
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Objective {
+    // The fitness set by whoever evaluated the genome
+    Fitness,
+    // Sparseness of the genome's behavior compared to the population and the novelty archive
+    Novelty,
+    // Weighted mix of novelty and fitness, both scaled to 0..1 first. 1.0 is pure novelty.
+    Blend(f64)
+}
+
+pub struct Config {
+    // How parents are picked within a species
+    pub selection: Box<Selection>,
+    // Chance that the second parent comes from another species
+    pub interspecies_rate: f64,
+    pub objective: Objective,
+    // Objective score that stats, the champion and the hall of fame go by when genomes carry
+    // several. Pareto ranks only compare genomes within one generation.
+    pub primary_objective: usize,
+    pub novelty_neighbours: usize,
+    pub novelty_threshold: f64,
+    // Number of best genomes kept over the whole run
+    pub hall_of_fame_size: usize,
+    // Runs with the same seed and config produce the same genomes. None seeds from the OS.
+    pub seed: Option<usize>
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config {
+            selection: Box::new(Tournament { size: TOURNAMENT_SIZE }),
+            interspecies_rate: INTERSPECIES_RATE,
+            objective: Objective::Fitness,
+            primary_objective: 0,
+            novelty_neighbours: NOVELTY_NEIGHBOURS,
+            novelty_threshold: NOVELTY_THRESHOLD,
+            hall_of_fame_size: HALL_OF_FAME_SIZE,
+            seed: None
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Config> {
+        let mut text = String::new();
+        try!(try!(File::open(path)).read_to_string(&mut text));
+        Config::parse(&text)
+    }
+
+    // One `key = value` per line, `#` starts a comment. Keys that aren't given keep their
+    // defaults, for example:
+    //   selection = tournament 5
+    //   objective = blend 0.5
+    //   interspecies_rate = 0.01
+    //   seed = 42
+    pub fn parse(text: &str) -> io::Result<Config> {
+        let mut config = Config::new();
+
+        for line in text.lines() {
+            let line = line.splitn(2, '#').next().unwrap_or("").trim();
+            if line.len() == 0 {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = try!(parts.next().ok_or(invalid(line))).trim();
+            let words: Vec<&str> = value.split_whitespace().collect();
+
+            match key {
+                "selection" => config.selection = match (words.get(0), words.get(1)) {
+                    (Some(&"tournament"), size) =>
+                        Box::new(Tournament { size: try!(parse_or(size, TOURNAMENT_SIZE)) }),
+                    (Some(&"roulette"), None) => Box::new(Roulette),
+                    (Some(&"rank"), None) => Box::new(Rank),
+                    (Some(&"uniform"), None) => Box::new(Uniform),
+                    _ => return Err(invalid(line))
+                },
+                "objective" => config.objective = match (words.get(0), words.get(1)) {
+                    (Some(&"fitness"), None) => Objective::Fitness,
+                    (Some(&"novelty"), None) => Objective::Novelty,
+                    (Some(&"blend"), weight) => Objective::Blend(try!(parse_or(weight, 0.5))),
+                    _ => return Err(invalid(line))
+                },
+                "primary_objective" => config.primary_objective = try!(parse_value(value)),
+                "interspecies_rate" => config.interspecies_rate = try!(parse_value(value)),
+                "novelty_neighbours" => config.novelty_neighbours = try!(parse_value(value)),
+                "novelty_threshold" => config.novelty_threshold = try!(parse_value(value)),
+                "hall_of_fame_size" => config.hall_of_fame_size = try!(parse_value(value)),
+                "seed" => config.seed = Some(try!(parse_value(value))),
+                _ => return Err(invalid(line))
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn invalid(line: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Bad config line: {}", line))
+}
+
+fn parse_value<T: FromStr>(value: &str) -> io::Result<T> {
+    value.parse().map_err(|_| invalid(value))
+}
+
+fn parse_or<T: FromStr>(value: Option<&&str>, default: T) -> io::Result<T> {
+    match value {
+        Some(value) => parse_value(value),
+        None => Ok(default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_parses_overrides() {
+        let config = Config::parse("
+            # Comments and blank lines are skipped
+            objective = blend 0.25
+            selection = uniform
+            hall_of_fame_size = 3
+            seed = 7
+        ").unwrap();
+
+        assert!(config.objective == Objective::Blend(0.25));
+        assert!(config.hall_of_fame_size == 3);
+        assert!(config.seed == Some(7));
+        assert!(config.interspecies_rate == INTERSPECIES_RATE);
+    }
+
+    #[test]
+    fn config_rejects_unknown_keys() {
+        assert!(Config::parse("mutation = lots").is_err());
+    }
+}
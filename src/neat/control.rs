@@ -0,0 +1,258 @@
+// Deciding when an evolution run is over
+
+use neat::Creator;
+use neat::genetics::Genome;
+use neat::population::Population;
+use neat::stats::GenerationStats;
+use neat::task::Task;
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+// Anything that can be evolved one generation at a time
+pub trait Evolve {
+    fn generation(&mut self) -> GenerationStats;
+    // Best genome seen so far
+    fn champion(&self) -> Option<&Genome>;
+}
+
+// A population evolving on a task
+pub struct TaskRun<T: Task> {
+    pub task: T,
+    pub population: Population
+}
+
+impl<T: Task> TaskRun<T> {
+    pub fn new(task: T, size: usize, creator: Creator) -> TaskRun<T> {
+        let population = Population::new(&task, size, creator);
+        TaskRun { task: task, population: population }
+    }
+}
+
+impl<T: Task> Evolve for TaskRun<T> {
+    fn generation(&mut self) -> GenerationStats {
+        self.population.step(&mut self.task)
+    }
+
+    fn champion(&self) -> Option<&Genome> {
+        self.population.champion.as_ref()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StopReason {
+    TargetReached,
+    // No improvement on the best fitness for the configured number of generations
+    Stagnated,
+    OutOfTime,
+    GenerationLimit
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match *self {
+            StopReason::TargetReached => "target fitness reached",
+            StopReason::Stagnated => "no improvement",
+            StopReason::OutOfTime => "out of time",
+            StopReason::GenerationLimit => "generation limit reached"
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+// Conditions that end a run, whichever is met first. None disables a condition.
+#[derive(Clone, Debug)]
+pub struct StoppingCriteria {
+    pub target_fitness: Option<f64>,
+    pub stagnation: Option<u64>,
+    pub time_budget: Option<Duration>,
+    pub max_generations: Option<u64>
+}
+
+impl StoppingCriteria {
+    // Never stops
+    pub fn new() -> StoppingCriteria {
+        StoppingCriteria {
+            target_fitness: None,
+            stagnation: None,
+            time_budget: None,
+            max_generations: None
+        }
+    }
+
+    // Sets the criterion behind a command line flag such as `--target`. Ok(false) when the flag
+    // isn't one of these.
+    pub fn parse_flag(&mut self, flag: &str, value: &str) -> Result<bool, String> {
+        match flag {
+            "--generations" => self.max_generations = Some(try!(parse_value(flag, value))),
+            "--target" => self.target_fitness = Some(try!(parse_value(flag, value))),
+            "--stagnation" => self.stagnation = Some(try!(parse_value(flag, value))),
+            "--time-budget" => self.time_budget = Some(Duration::from_secs(try!(parse_value(flag, value)))),
+            _ => return Ok(false)
+        }
+        Ok(true)
+    }
+}
+
+fn parse_value<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Bad value for {}: {}", flag, value))
+}
+
+pub struct Outcome {
+    pub reason: StopReason,
+    pub generations: u64,
+    pub champion: Option<Genome>
+}
+
+pub struct RunController {
+    pub criteria: StoppingCriteria,
+    started: Instant,
+    generations: u64,
+    best_fitness: Option<f64>,
+    // Generations since the best fitness last went up
+    stale: u64
+}
+
+impl RunController {
+    // The clock starts now
+    pub fn new(criteria: StoppingCriteria) -> RunController {
+        RunController {
+            criteria: criteria,
+            started: Instant::now(),
+            generations: 0,
+            best_fitness: None,
+            stale: 0
+        }
+    }
+
+    pub fn generations(&self) -> u64 {
+        self.generations
+    }
+
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.best_fitness
+    }
+
+    // Call with the stats of every finished generation, returns why the run should stop
+    pub fn check(&mut self, stats: &GenerationStats) -> Option<StopReason> {
+        self.generations += 1;
+        match self.best_fitness {
+            Some(best) if stats.max_fitness <= best => self.stale += 1,
+            _ => {
+                self.best_fitness = Some(stats.max_fitness);
+                self.stale = 0;
+            }
+        }
+
+        if let Some(target) = self.criteria.target_fitness {
+            if stats.max_fitness >= target {
+                return Some(StopReason::TargetReached);
+            }
+        }
+        if let Some(stagnation) = self.criteria.stagnation {
+            if self.stale >= stagnation {
+                return Some(StopReason::Stagnated);
+            }
+        }
+        if let Some(budget) = self.criteria.time_budget {
+            if self.started.elapsed() >= budget {
+                return Some(StopReason::OutOfTime);
+            }
+        }
+        if let Some(max_generations) = self.criteria.max_generations {
+            if self.generations >= max_generations {
+                return Some(StopReason::GenerationLimit);
+            }
+        }
+
+        None
+    }
+
+    // Evolve until one of the criteria is met. Without any criteria this never returns.
+    pub fn run<E: Evolve + ?Sized>(&mut self, evolve: &mut E) -> Outcome {
+        loop {
+            let stats = evolve.generation();
+            if let Some(reason) = self.check(&stats) {
+                info!("Stopped after {} generations: {}", self.generations, reason);
+                return Outcome {
+                    reason: reason,
+                    generations: self.generations,
+                    champion: evolve.champion().map(|champion| champion.clone())
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat::genetics::{Gene, Genome};
+    use neat::stats::GenerationStats;
+
+    use std::time::Duration;
+
+    // Best fitness follows a fixed sequence
+    struct Scripted {
+        fitnesses: Vec<f64>,
+        generation: usize
+    }
+
+    impl Evolve for Scripted {
+        fn generation(&mut self) -> GenerationStats {
+            let mut genome = Genome::new(vec![
+                Gene{ into: 0, out: 1, weight: 1.0, enabled: true, innovation: 1 }
+            ], 1, 1);
+            genome.fitness = self.fitnesses[self.generation];
+            self.generation += 1;
+            GenerationStats::new(self.generation as u64, &[&genome], &[genome.fitness])
+        }
+
+        fn champion(&self) -> Option<&Genome> {
+            None
+        }
+    }
+
+    fn outcome(criteria: StoppingCriteria, fitnesses: Vec<f64>) -> Outcome {
+        let mut scripted = Scripted { fitnesses: fitnesses, generation: 0 };
+        RunController::new(criteria).run(&mut scripted)
+    }
+
+    #[test]
+    fn controller_stops_on_first_criterion_met() {
+        let mut criteria = StoppingCriteria::new();
+        criteria.target_fitness = Some(5f64);
+        criteria.max_generations = Some(4);
+
+        let reached = outcome(criteria.clone(), vec![1f64, 2f64, 6f64, 7f64]);
+        assert!(reached.reason == StopReason::TargetReached);
+        assert!(reached.generations == 3);
+
+        let limited = outcome(criteria, vec![1f64, 2f64, 3f64, 4f64]);
+        assert!(limited.reason == StopReason::GenerationLimit);
+        assert!(limited.generations == 4);
+    }
+
+    #[test]
+    fn controller_stops_on_stagnation() {
+        let mut criteria = StoppingCriteria::new();
+        criteria.stagnation = Some(2);
+
+        let stagnated = outcome(criteria, vec![1f64, 3f64, 2f64, 3f64, 4f64]);
+        assert!(stagnated.reason == StopReason::Stagnated);
+        assert!(stagnated.generations == 4);
+    }
+
+    #[test]
+    fn criteria_parse_flags() {
+        let mut criteria = StoppingCriteria::new();
+        assert!(criteria.parse_flag("--target", "3.5") == Ok(true));
+        assert!(criteria.parse_flag("--time-budget", "60") == Ok(true));
+        assert!(criteria.parse_flag("--seed", "1") == Ok(false));
+        assert!(criteria.parse_flag("--generations", "ten").is_err());
+        assert!(criteria.target_fitness == Some(3.5));
+        assert!(criteria.time_budget == Some(Duration::from_secs(60)));
+        assert!(criteria.max_generations.is_none());
+    }
+}
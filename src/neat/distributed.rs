@@ -0,0 +1,397 @@
+// Genome evaluation spread over worker processes connected over TCP.
+//
+// Workers connect to the coordinator and then wait for jobs. Everything is plain text, one
+// message per line:
+//
+//   coordinator -> worker:  EVAL <job id>
+//                           <genome, as written by Genome::write>
+//                           END
+//   worker -> coordinator:  RESULT <job id> <fitness> <n> <n objectives> <m> <m behavior values>
+//
+// A worker handles one job at a time and exits when the coordinator closes the connection. If a
+// worker disconnects or takes longer than the timeout to answer, the coordinator drops it and
+// hands its job to another worker. If there are no workers at all for too long the coordinator
+// gives up.
+
+use neat::genetics::Genome;
+use neat::task::{Evaluation, Task};
+
+use std::collections::VecDeque;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How often the coordinator checks for new workers while it has nobody to give work to
+const POLL_MS: u64 = 50;
+// How long the coordinator waits without any workers by default
+const PATIENCE_MS: u64 = 60000;
+
+pub struct Coordinator {
+    addr: SocketAddr,
+    idle: Arc<Mutex<Vec<TcpStream>>>,
+    // Set when the coordinator is dropped, workers connecting after that are turned away
+    stopped: Arc<AtomicBool>,
+    timeout: Duration,
+    patience: Duration
+}
+
+impl Coordinator {
+    // Starts accepting workers in the background
+    pub fn bind<A: ToSocketAddrs>(addr: A, timeout: Duration) -> io::Result<Coordinator> {
+        let listener = try!(TcpListener::bind(addr));
+        let local_addr = try!(listener.local_addr());
+        let idle = Arc::new(Mutex::new(Vec::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let accepted = idle.clone();
+        let stopping = stopped.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        // Checked under the lock so a worker can't slip in after the hang up
+                        let mut idle = accepted.lock().unwrap();
+                        if stopping.load(Ordering::SeqCst) {
+                            let _ = stream.shutdown(Shutdown::Both);
+                            break;
+                        }
+                        info!("Worker connected from {:?}", stream.peer_addr());
+                        idle.push(stream);
+                    },
+                    Err(e) => warn!("Failed to accept worker: {}", e)
+                }
+            }
+        });
+
+        Ok(Coordinator {
+            addr: local_addr,
+            idle: idle,
+            stopped: stopped,
+            timeout: timeout,
+            patience: Duration::from_millis(PATIENCE_MS)
+        })
+    }
+
+    // How long `evaluate` waits while no worker is connected before it gives up
+    pub fn set_patience(&mut self, patience: Duration) {
+        self.patience = patience;
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // Workers connected and waiting for a job
+    pub fn idle_workers(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+
+    // Sets the fitness, objectives and behavior of every genome, blocks until all of them have
+    // been evaluated. Fails when no worker has been connected for as long as the patience lasts,
+    // genomes evaluated by then keep their results.
+    pub fn evaluate(&mut self, genomes: &mut [Genome]) -> io::Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        let mut pending: VecDeque<usize> = (0..genomes.len()).collect();
+        let mut remaining = genomes.len();
+        let mut busy = 0;
+        let mut last_busy = Instant::now();
+
+        while remaining > 0 {
+            // Hand out as much work as there are idle workers
+            while pending.len() > 0 {
+                let mut worker = match self.idle.lock().unwrap().pop() {
+                    Some(worker) => worker,
+                    None => break
+                };
+                let job = pending.pop_front().unwrap();
+                let mut message = format!("EVAL {}\n", job).into_bytes();
+                genomes[job].write(&mut message).unwrap();
+                message.extend(b"END\n".iter());
+
+                let sender = sender.clone();
+                let timeout = self.timeout;
+                thread::spawn(move || {
+                    let result = run_job(&mut worker, job, &message, timeout);
+                    let _ = sender.send((worker, job, result));
+                });
+                busy += 1;
+            }
+
+            if busy == 0 {
+                // Nobody to give work to, wait for workers to connect
+                if last_busy.elapsed() >= self.patience {
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "No workers connected"));
+                }
+                thread::sleep(Duration::from_millis(POLL_MS));
+                continue;
+            }
+            last_busy = Instant::now();
+
+            match receiver.recv_timeout(Duration::from_millis(POLL_MS)) {
+                Ok((worker, job, Ok(evaluation))) => {
+                    genomes[job].fitness = evaluation.fitness;
+                    genomes[job].objectives = evaluation.objectives;
+                    genomes[job].behavior = evaluation.behavior;
+                    remaining -= 1;
+                    busy -= 1;
+                    self.idle.lock().unwrap().push(worker);
+                },
+                Ok((_, job, Err(e))) => {
+                    // Drop the worker, someone else gets the job
+                    warn!("Worker failed on job {}, reassigning: {}", job, e);
+                    pending.push_back(job);
+                    busy -= 1;
+                },
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+// Hang up on waiting workers so they exit and stop the accept thread, which is woken by
+// connecting to it
+impl Drop for Coordinator {
+    fn drop(&mut self) {
+        {
+            let mut idle = self.idle.lock().unwrap();
+            self.stopped.store(true, Ordering::SeqCst);
+            for worker in idle.drain(..) {
+                let _ = worker.shutdown(Shutdown::Both);
+            }
+        }
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn run_job(worker: &mut TcpStream, job: usize, message: &[u8], timeout: Duration) -> io::Result<Evaluation> {
+    let deadline = Instant::now() + timeout;
+    try!(worker.write_all(message));
+
+    let line = try!(read_line(worker, deadline));
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    match (fields.get(0), fields.get(1).and_then(|id| id.parse::<usize>().ok())) {
+        (Some(&"RESULT"), Some(id)) if id == job => parse_result(&fields[2..]).ok_or(bad_message(&line)),
+        _ => Err(bad_message(&line))
+    }
+}
+
+// `<fitness> <n> <n objectives> <m> <m behavior values>`
+fn parse_result(fields: &[&str]) -> Option<Evaluation> {
+    let values: Vec<f64> = fields.iter().filter_map(|field| field.parse().ok()).collect();
+    if values.len() != fields.len() || values.len() < 3 {
+        return None;
+    }
+
+    let mut evaluation = Evaluation::new(values[0]);
+    let num_objectives = values[1] as usize;
+    if values.len() < 3 + num_objectives {
+        return None;
+    }
+    evaluation.objectives = values[2..2 + num_objectives].to_vec();
+    let rest = &values[2 + num_objectives..];
+    if rest[0] as usize != rest.len() - 1 {
+        return None;
+    }
+    evaluation.behavior = rest[1..].to_vec();
+    Some(evaluation)
+}
+
+// Reads a byte at a time so nothing past the line is taken off the stream. The whole line has to
+// arrive by the deadline, however it is split up.
+fn read_line(stream: &mut TcpStream, deadline: Instant) -> io::Result<String> {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "Worker took too long"));
+        }
+        try!(stream.set_read_timeout(Some(deadline - now)));
+        if try!(stream.read(&mut byte)) == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Worker disconnected"));
+        }
+        if byte[0] == b'\n' {
+            break;
+        }
+        line.push(byte[0]);
+    }
+    String::from_utf8(line).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Not UTF-8"))
+}
+
+// Objectives and behavior, each prefixed by how many values there are
+fn format_result(evaluation: &Evaluation) -> String {
+    let mut fields = vec![evaluation.objectives.len().to_string()];
+    fields.extend(evaluation.objectives.iter().map(|value| value.to_string()));
+    fields.push(evaluation.behavior.len().to_string());
+    fields.extend(evaluation.behavior.iter().map(|value| value.to_string()));
+    fields.join(" ")
+}
+
+fn bad_message(line: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Bad message: {}", line))
+}
+
+// Evaluates jobs from the coordinator at `addr` until it hangs up, returns the number of jobs done
+pub fn work<A: ToSocketAddrs, T: Task + ?Sized>(addr: A, task: &mut T) -> io::Result<u64> {
+    let mut stream = try!(TcpStream::connect(addr));
+    let mut reader = BufReader::new(try!(stream.try_clone()));
+    let mut jobs = 0;
+
+    loop {
+        let mut line = String::new();
+        if try!(reader.read_line(&mut line)) == 0 {
+            return Ok(jobs);
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let job: usize = match (fields.get(0), fields.get(1)) {
+            (Some(&"EVAL"), Some(id)) => try!(id.parse().map_err(|_| bad_message(&line))),
+            _ => return Err(bad_message(&line))
+        };
+
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            if try!(reader.read_line(&mut line)) == 0 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Coordinator disconnected"));
+            }
+            if line.trim() == "END" {
+                break;
+            }
+            text.push_str(&line);
+        }
+
+        let genome = try!(Genome::parse(&text));
+        let evaluation = task.evaluate(&genome.network);
+        try!(writeln!(stream, "RESULT {} {} {}", job, evaluation.fitness, format_result(&evaluation)));
+        jobs += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat;
+    use neat::benchmarks::Xor;
+    use neat::genetics::Genome;
+    use neat::neurology::Network;
+    use neat::task::{Evaluation, Task};
+
+    use std::io;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn jobs_of_lost_workers_are_reassigned() {
+        let mut coordinator = Coordinator::bind("127.0.0.1:0", Duration::from_millis(200)).unwrap();
+        let addr = coordinator.local_addr();
+
+        // Takes a job and hangs up without answering
+        let mut quitter = TcpStream::connect(addr).unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 16];
+            let _ = quitter.read(&mut buffer);
+        });
+        // Takes a job and never answers
+        let _silent = TcpStream::connect(addr).unwrap();
+        // Takes a job and keeps the line alive without ever finishing the answer
+        let mut trickler = TcpStream::connect(addr).unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 16];
+            let _ = trickler.read(&mut buffer);
+            while trickler.write_all(b"R").is_ok() {
+                thread::sleep(Duration::from_millis(50));
+            }
+        });
+        while coordinator.idle_workers() < 3 {
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::spawn(move || work(addr, &mut Xor).unwrap());
+
+        let mut rng = neat::seeded_rng(1);
+        let mut genomes: Vec<Genome> = (0..4).map(|_| Genome::random(3, 1, &mut rng)).collect();
+        coordinator.evaluate(&mut genomes).unwrap();
+
+        for genome in genomes.iter() {
+            assert!(genome.fitness == Xor.evaluate(&genome.network).fitness);
+        }
+    }
+
+    // Scores on two objectives and describes its behavior by the network's output
+    struct Described;
+
+    impl Task for Described {
+        fn num_inputs(&self) -> u64 { 1 }
+        fn num_outputs(&self) -> u64 { 1 }
+
+        fn evaluate(&mut self, network: &Network) -> Evaluation {
+            let output = network.evaluate(vec![1f64])[0];
+            let mut evaluation = Evaluation::new(output);
+            evaluation.objectives = vec![output, -output];
+            evaluation.behavior = vec![output, 2f64 * output, 0.5];
+            evaluation
+        }
+    }
+
+    #[test]
+    fn workers_send_objectives_and_behavior() {
+        let mut coordinator = Coordinator::bind("127.0.0.1:0", Duration::from_secs(5)).unwrap();
+        let addr = coordinator.local_addr();
+        let worker = thread::spawn(move || work(addr, &mut Described).unwrap());
+
+        let mut rng = neat::seeded_rng(1);
+        let mut genomes: Vec<Genome> = (0..3).map(|_| Genome::random(1, 1, &mut rng)).collect();
+        coordinator.evaluate(&mut genomes).unwrap();
+        for genome in genomes.iter() {
+            let evaluation = Described.evaluate(&genome.network);
+            assert!(genome.objectives == evaluation.objectives);
+            assert!(genome.behavior == evaluation.behavior);
+        }
+
+        drop(coordinator);
+        assert!(worker.join().unwrap() == 3);
+    }
+
+    #[test]
+    fn evaluation_gives_up_without_workers() {
+        let mut coordinator = Coordinator::bind("127.0.0.1:0", Duration::from_secs(5)).unwrap();
+        coordinator.set_patience(Duration::from_millis(200));
+
+        let mut rng = neat::seeded_rng(1);
+        let mut genomes: Vec<Genome> = (0..2).map(|_| Genome::random(1, 1, &mut rng)).collect();
+        match coordinator.evaluate(&mut genomes) {
+            Err(e) => assert!(e.kind() == io::ErrorKind::TimedOut),
+            Ok(_) => panic!("Evaluated without workers")
+        }
+
+        // The only worker drops out halfway through its job
+        let addr = coordinator.local_addr();
+        let mut quitter = TcpStream::connect(addr).unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 16];
+            let _ = quitter.read(&mut buffer);
+        });
+        assert!(coordinator.evaluate(&mut genomes).is_err());
+    }
+
+    #[test]
+    fn workers_arriving_after_drop_are_turned_away() {
+        let coordinator = Coordinator::bind("127.0.0.1:0", Duration::from_secs(5)).unwrap();
+        let addr = coordinator.local_addr();
+        drop(coordinator);
+
+        // Either hung up on or refused, but never left waiting
+        match work(addr, &mut Described) {
+            Ok(jobs) => assert!(jobs == 0),
+            Err(_) => {}
+        }
+    }
+}
@@ -1,13 +1,19 @@
-use neat;
 use neat::neurology::Network;
 
-use rand::Rng;
+use rand::{Rng, StdRng};
 use rand::distributions::{IndependentSample, Range};
 
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+use std::str::FromStr;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub struct Gene {
     pub into: u64,
     pub out: u64,
@@ -23,11 +29,32 @@ impl fmt::Debug for Gene {
     }
 }
 
-static mut INNOVATION: u64 = 0;
+// Hands out the historical markings of new genes. Every run keeps its own so runs in the same
+// process don't affect each other's numbering. Clones hand out numbers from the same counter, for
+// populations whose genomes meet, like islands swapping migrants.
+#[derive(Clone, Debug)]
+pub struct Innovations {
+    current: Rc<Cell<u64>>
+}
+
+impl Innovations {
+    pub fn new() -> Innovations {
+        Innovations::starting_at(0)
+    }
+
+    // Carries on after numbers that are already taken
+    pub fn starting_at(current: u64) -> Innovations {
+        Innovations { current: Rc::new(Cell::new(current)) }
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.current.set(self.current.get() + 1);
+        self.current.get()
+    }
 
-unsafe fn innovation_next() -> u64 {
-    INNOVATION += 1;
-    return INNOVATION;
+    pub fn current(&self) -> u64 {
+        self.current.get()
+    }
 }
 
 const MUTATE_CROSSOVER: f64 = 0.75;
@@ -66,6 +93,11 @@ impl MutationRates {
 pub struct Genome {
     pub genes: Vec<Gene>,
     pub fitness: f64,
+    // Scores for multi-objective runs, each one higher is better. Empty when fitness is all that
+    // matters.
+    pub objectives: Vec<f64>,
+    // What the genome did while being evaluated, used by novelty search
+    pub behavior: Vec<f64>,
     pub network: Network,
     num_inputs: u64,
     num_outputs: u64,
@@ -86,6 +118,8 @@ impl Genome {
             genes: genes,
             network: network,
             fitness: 0.0,
+            objectives: Vec::new(),
+            behavior: Vec::new(),
             num_inputs: num_inputs,
             num_outputs: num_outputs,
             mutation_rates: MutationRates::new()
@@ -98,6 +132,8 @@ impl Genome {
         let clone = Genome {
             genes: clone_genes,
             fitness: self.fitness,
+            objectives: self.objectives.clone(),
+            behavior: self.behavior.clone(),
             network: clone_network,
             num_inputs: self.num_inputs,
             num_outputs: self.num_outputs,
@@ -106,17 +142,16 @@ impl Genome {
         return clone;
     }
 
-    pub fn random(num_inputs: u64, num_outputs: u64) -> Genome {
+    pub fn random(num_inputs: u64, num_outputs: u64, rng: &mut StdRng) -> Genome {
         let mut genes = Vec::new();
         let num_genes = Range::new(1u64, 5u64);
         let num_neurons = Range::new(1u64, 7u64);
         let weights = Range::new(-1f64, 1f64);
-        let mut rng = neat::rng();
-        for i in 0..num_genes.ind_sample(&mut rng) {
+        for i in 0..num_genes.ind_sample(rng) {
             let gene = Gene {
-                into: num_neurons.ind_sample(&mut rng),
-                out: num_neurons.ind_sample(&mut rng),
-                weight: weights.ind_sample(&mut rng),
+                into: num_neurons.ind_sample(rng),
+                out: num_neurons.ind_sample(rng),
+                weight: weights.ind_sample(rng),
                 enabled: true,
                 innovation: i
             };
@@ -126,66 +161,60 @@ impl Genome {
         return Genome::new(genes, num_inputs, num_outputs);
     }
 
-    pub fn breed(&self, genome: &Genome) -> Genome {
+    pub fn breed(&self, genome: &Genome, rng: &mut StdRng, innovations: &mut Innovations) -> Genome {
         debug!("Breeding genomes");
         let mut child = self.clone();
 
-        let mut rng = neat::rng();
-        if Range::new(0f64, 1f64).ind_sample(&mut rng) > self.mutation_rates.crossover {
+        if Range::new(0f64, 1f64).ind_sample(rng) > self.mutation_rates.crossover {
             debug!("Crossing genomes");
-            child = self.cross(genome);
+            child = self.cross(genome, rng);
         }
 
-        child.mutate();
+        child.mutate(rng, innovations);
         return child;
     }
 
-    pub fn mutate(&mut self) {
-        let mut rng = neat::rng();
-
+    pub fn mutate(&mut self, rng: &mut StdRng, innovations: &mut Innovations) {
         let zero_to_one = Range::new(0f64, 1f64);
-        if zero_to_one.ind_sample(&mut rng) < self.mutation_rates.weight {
-            self.mutate_weight();
+        if zero_to_one.ind_sample(rng) < self.mutation_rates.weight {
+            self.mutate_weight(rng);
         }
 
         let zero_to_one = Range::new(0f64, 1f64);
-        if zero_to_one.ind_sample(&mut rng) < self.mutation_rates.link {
-            self.mutate_link();
+        if zero_to_one.ind_sample(rng) < self.mutation_rates.link {
+            self.mutate_link(rng, innovations);
         }
 
         let zero_to_one = Range::new(0f64, 1f64);
-        if zero_to_one.ind_sample(&mut rng) < self.mutation_rates.node {
-            self.mutate_node();
+        if zero_to_one.ind_sample(rng) < self.mutation_rates.node {
+            self.mutate_node(rng, innovations);
         }
     }
 
-    pub fn mutate_weight(&mut self) {
-        let mut rng = neat::rng();
-
+    pub fn mutate_weight(&mut self, rng: &mut StdRng) {
         let num_genes = Range::new(0usize, self.genes.len());
-        let gene = num_genes.ind_sample(&mut rng);
+        let gene = num_genes.ind_sample(rng);
 
         let zero_to_one = Range::new(0f64, 1f64);
-        if zero_to_one.ind_sample(&mut rng) < self.mutation_rates.weight_new {
-            let new_weight = zero_to_one.ind_sample(&mut rng) * 2f64 - 1f64;
+        if zero_to_one.ind_sample(rng) < self.mutation_rates.weight_new {
+            let new_weight = zero_to_one.ind_sample(rng) * 2f64 - 1f64;
             debug!("Mutating weight of gene #{} to {}", gene, new_weight);
             self.genes[gene].weight = new_weight;
         } else {
             let weight_step_range = Range::new(
                 -self.mutation_rates.weight_step,
                 self.mutation_rates.weight_step);
-            let weight_step = weight_step_range.ind_sample(&mut rng) + 1f64;
+            let weight_step = weight_step_range.ind_sample(rng) + 1f64;
 
             debug!("Mutating weight of gene #{} by {:.2}%", gene, weight_step);
             self.genes[gene].weight *= weight_step;
         }
     }
 
-    pub fn mutate_link(&mut self) {
-        let mut rng = neat::rng();
+    pub fn mutate_link(&mut self, rng: &mut StdRng, innovations: &mut Innovations) {
         let neuron_range = Range::new(0u64, self.network.neurons.keys().len() as u64);
-        let mut neuron1 = neuron_range.ind_sample(&mut rng);
-        let mut neuron2 = neuron_range.ind_sample(&mut rng);
+        let mut neuron1 = neuron_range.ind_sample(rng);
+        let mut neuron2 = neuron_range.ind_sample(rng);
 
         // Both input nodes
         if neuron1 <= self.num_inputs - 1 && neuron2 <= self.num_inputs - 1 {
@@ -209,11 +238,10 @@ impl Genome {
         // Doesn't point to self
         if neuron1 == neuron2 { return; }
 
-        let innovation;
-        unsafe { innovation = innovation_next(); }
+        let innovation = innovations.next();
 
         let zero_to_one = Range::new(0f64, 1f64);
-        let weight = zero_to_one.ind_sample(&mut rng) * 2f64 - 1f64;
+        let weight = zero_to_one.ind_sample(rng) * 2f64 - 1f64;
 
         let gene = Gene {
             into: neuron1,
@@ -228,33 +256,34 @@ impl Genome {
 
     }
 
-    pub fn mutate_node(&mut self) {
+    pub fn mutate_node(&mut self, rng: &mut StdRng, innovations: &mut Innovations) {
         if self.genes.len() == 0 { return; }
 
-        let mut rng = neat::rng();
         let gene_range = Range::new(0, self.genes.len());
-        let mut gene = self.genes[gene_range.ind_sample(&mut rng)];
-
-        gene.enabled = false;
+        let idx = gene_range.ind_sample(rng);
+        self.genes[idx].enabled = false;
+        let gene = self.genes[idx];
 
-        let maxneuron = *(self.network.neurons.keys().max().expect("BRAIN DAMAGE"));
-        debug!("Mutating new node between {} -> {}", gene.into, gene.out);
+        // The network isn't rebuilt between mutations, the genes know about every neuron
+        let newneuron = self.genes.iter().fold(self.num_inputs + self.num_outputs - 1,
+            |acc, gene| acc.max(gene.into).max(gene.out)) + 1;
+        debug!("Mutating new node #{} between {} -> {}", newneuron, gene.into, gene.out);
 
         let mut gene1 = gene.clone();
-        gene1.out = maxneuron;
+        gene1.out = newneuron;
         gene1.weight = 1.0;
-        unsafe { gene1.innovation = innovation_next(); }
+        gene1.innovation = innovations.next();
         gene1.enabled = true;
         self.genes.push(gene1);
 
         let mut gene2 = gene.clone();
-        gene2.into = maxneuron;
-        unsafe { gene2.innovation = innovation_next(); }
+        gene2.into = newneuron;
+        gene2.innovation = innovations.next();
         gene2.enabled = true;
         self.genes.push(gene2);
     }
 
-    pub fn cross(&self, genome: &Genome) -> Genome {
+    pub fn cross(&self, genome: &Genome, rng: &mut StdRng) -> Genome {
         let mut genome1 = self;
         let mut genome2 = genome;
         if self.fitness < genome.fitness {
@@ -269,7 +298,6 @@ impl Genome {
         }
 
         // Cross genomes
-        let mut rng = neat::rng();
         let mut child_genes: Vec<Gene> = Vec::new();
         for gene1 in genome1.genes.iter() {
             let mut gene = gene1.clone();
@@ -280,7 +308,7 @@ impl Genome {
                 }
                 if !gene1.enabled || !gene2.enabled {
                     let zero_to_one = Range::new(0f64, 1f64);
-                    if zero_to_one.ind_sample(&mut rng) < self.mutation_rates.disable {
+                    if zero_to_one.ind_sample(rng) < self.mutation_rates.disable {
                         gene.enabled = false;
                     }
                 }
@@ -292,6 +320,8 @@ impl Genome {
         let child = Genome {
             genes: child_genes,
             fitness: 0f64,
+            objectives: Vec::new(),
+            behavior: Vec::new(),
             network: network,
             num_inputs: genome1.num_inputs,
             num_outputs: genome1.num_outputs,
@@ -300,6 +330,93 @@ impl Genome {
 
         return child;
     }
+
+    // Plain text dump, one line for the genome followed by one line per gene:
+    //   genome <inputs> <outputs> <fitness>
+    //   gene <innovation> <into> <out> <weight> <enabled>
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        try!(writeln!(out, "genome {} {} {}", self.num_inputs, self.num_outputs, self.fitness));
+        for gene in self.genes.iter() {
+            try!(writeln!(out, "gene {} {} {} {} {}",
+                          gene.innovation, gene.into, gene.out, gene.weight, gene.enabled));
+        }
+        Ok(())
+    }
+
+    // Reads back what `write` wrote
+    pub fn parse(text: &str) -> io::Result<Genome> {
+        let mut header: Option<(u64, u64, f64)> = None;
+        let mut genes = Vec::new();
+
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.get(0) {
+                Some(&"genome") if fields.len() == 4 => {
+                    header = Some((try!(parse_field(fields[1])), try!(parse_field(fields[2])),
+                                   try!(parse_field(fields[3]))));
+                },
+                Some(&"gene") if fields.len() == 6 => {
+                    genes.push(Gene {
+                        innovation: try!(parse_field(fields[1])),
+                        into: try!(parse_field(fields[2])),
+                        out: try!(parse_field(fields[3])),
+                        weight: try!(parse_field(fields[4])),
+                        enabled: try!(parse_field(fields[5]))
+                    });
+                },
+                None => continue,
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                               format!("Bad genome line: {}", line)))
+            }
+        }
+
+        match header {
+            Some((num_inputs, num_outputs, fitness)) => {
+                let mut genome = Genome::new(genes, num_inputs, num_outputs);
+                genome.fitness = fitness;
+                Ok(genome)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidData, "Missing genome header"))
+        }
+    }
+
+    // Reads back several genomes written one after the other
+    pub fn parse_all(text: &str) -> io::Result<Vec<Genome>> {
+        let mut genomes = Vec::new();
+        let mut chunk = String::new();
+        for line in text.lines() {
+            if line.starts_with("genome ") && chunk.len() > 0 {
+                genomes.push(try!(Genome::parse(&chunk)));
+                chunk.clear();
+            }
+            chunk.push_str(line);
+            chunk.push('\n');
+        }
+        if chunk.trim().len() > 0 {
+            genomes.push(try!(Genome::parse(&chunk)));
+        }
+        Ok(genomes)
+    }
+
+    // Hash of the network the genome builds, weights are rounded so float noise doesn't count
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.num_inputs.hash(&mut hasher);
+        self.num_outputs.hash(&mut hasher);
+        for gene in self.genes.iter() {
+            gene.innovation.hash(&mut hasher);
+            gene.into.hash(&mut hasher);
+            gene.out.hash(&mut hasher);
+            gene.enabled.hash(&mut hasher);
+            ((gene.weight * 1e6).round() as i64).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+fn parse_field<T: FromStr>(field: &str) -> io::Result<T> {
+    field.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData,
+                                             format!("Bad genome field: {}", field)))
 }
 
 #[cfg(test)]
@@ -307,6 +424,7 @@ mod tests {
     extern crate env_logger;
 
     use super::*;
+    use neat;
 
     #[test]
     fn genomes_breed() {
@@ -322,7 +440,7 @@ mod tests {
             Gene{ into: 3, out: 2, weight: 1.0, enabled: true, innovation: 3 }
         ], 2, 1);
 
-        let child = genome1.breed(&genome2);
+        let child = genome1.breed(&genome2, &mut neat::seeded_rng(1), &mut Innovations::new());
 
         assert!(child.genes.len() > 0);
     }
@@ -330,7 +448,6 @@ mod tests {
     #[test]
     fn genome_crossover_preserves_innovation_ordering() {
         let _ = env_logger::init();
-        unsafe { super::INNOVATION = 6 }
         let genome1 = Genome::new(vec![
             Gene{ into: 0, out: 3, weight: 1.0, enabled: true, innovation: 1 },
             Gene{ into: 1, out: 3, weight: 1.0, enabled: true, innovation: 2 },
@@ -343,7 +460,7 @@ mod tests {
             Gene{ into: 3, out: 2, weight: 1.0, enabled: true, innovation: 6 }
         ], 2, 1);
 
-        let child = genome1.cross(&genome2);
+        let child = genome1.cross(&genome2, &mut neat::seeded_rng(1));
 
         let mut ordered = true;
         let mut innovation: u64 = 0;
@@ -357,4 +474,41 @@ mod tests {
         }
         assert!(ordered);
     }
+
+    #[test]
+    fn node_mutation_adds_a_new_neuron() {
+        let mut genome = Genome::new(vec![
+            Gene{ into: 0, out: 2, weight: 0.5, enabled: true, innovation: 1 }
+        ], 2, 1);
+        let mut rng = neat::seeded_rng(1);
+        let mut innovations = Innovations::new();
+        genome.mutate_node(&mut rng, &mut innovations);
+
+        // Neurons 0 to 2 are taken by the inputs and output, the split link is switched off
+        assert!(genome.genes.len() == 3);
+        assert!(!genome.genes[0].enabled);
+        assert!(genome.genes[1].into == 0 && genome.genes[1].out == 3 && genome.genes[1].enabled);
+        assert!(genome.genes[2].into == 3 && genome.genes[2].out == 2 && genome.genes[2].enabled);
+
+        // A second split before the network is rebuilt still gets a neuron of its own
+        genome.mutate_node(&mut rng, &mut innovations);
+        assert!(genome.genes.len() == 5);
+        assert!(genome.genes[3].out == 4 && genome.genes[4].into == 4);
+    }
+
+    #[test]
+    fn genomes_write_and_parse() {
+        let mut genome = Genome::new(vec![
+            Gene{ into: 0, out: 3, weight: 0.25, enabled: true, innovation: 1 },
+            Gene{ into: 3, out: 2, weight: -1.5, enabled: false, innovation: 3 }
+        ], 2, 1);
+        genome.fitness = -12.5;
+
+        let mut text = Vec::new();
+        genome.write(&mut text).unwrap();
+        let parsed = Genome::parse(&String::from_utf8(text).unwrap()).unwrap();
+
+        assert!(parsed.fitness == genome.fitness);
+        assert!(parsed.structural_hash() == genome.structural_hash());
+    }
 }
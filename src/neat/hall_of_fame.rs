@@ -0,0 +1,165 @@
+use neat::genetics::{Genome, Innovations};
+
+use rand::{Rng, StdRng};
+
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::Path;
+
+pub struct Entry {
+    pub genome: Genome,
+    pub generation: u64,
+    pub species_id: u64,
+    pub fitness: f64,
+    hash: u64
+}
+
+// The best distinct genomes seen over a whole run, best first
+pub struct HallOfFame {
+    capacity: usize,
+    entries: Vec<Entry>
+}
+
+impl HallOfFame {
+    pub fn new(capacity: usize) -> HallOfFame {
+        HallOfFame {
+            capacity: capacity,
+            entries: Vec::with_capacity(capacity + 1)
+        }
+    }
+
+    // Adds the genome if its `fitness` is good enough and it's not already in, returns whether it
+    // was added
+    pub fn consider(&mut self, genome: &Genome, fitness: f64, generation: u64, species_id: u64) -> bool {
+        if self.capacity == 0 || fitness.is_nan() {
+            return false;
+        }
+        if self.entries.len() == self.capacity && fitness <= self.entries[self.capacity - 1].fitness {
+            return false;
+        }
+
+        let hash = genome.structural_hash();
+        if let Some(pos) = self.entries.iter().position(|entry| entry.hash == hash) {
+            // Same genome evaluated again, keep whichever evaluation was better
+            if fitness <= self.entries[pos].fitness {
+                return false;
+            }
+            self.entries.remove(pos);
+        }
+
+        let pos = self.entries.iter().position(|entry| fitness > entry.fitness)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(pos, Entry {
+            genome: genome.clone(),
+            generation: generation,
+            species_id: species_id,
+            fitness: fitness,
+            hash: hash
+        });
+        self.entries.truncate(self.capacity);
+        true
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn best(&self) -> Option<&Entry> {
+        self.entries.first()
+    }
+
+    pub fn from_generation(&self, generation: u64) -> Vec<&Entry> {
+        self.entries.iter().filter(|entry| entry.generation == generation).collect()
+    }
+
+    pub fn from_species(&self, species_id: u64) -> Vec<&Entry> {
+        self.entries.iter().filter(|entry| entry.species_id == species_id).collect()
+    }
+
+    // Random member, e.g. as an opponent in competitive tasks
+    pub fn sample(&self, rng: &mut StdRng) -> Option<&Entry> {
+        rng.choose(&self.entries)
+    }
+
+    // Population of `size` genomes built from the members, every copy after the first of each
+    // member is mutated
+    pub fn seed(&self, size: usize, rng: &mut StdRng, innovations: &mut Innovations) -> Vec<Genome> {
+        if self.entries.len() == 0 {
+            return Vec::new();
+        }
+
+        (0..size).map(|i| {
+            let mut genome = self.entries[i % self.entries.len()].genome.clone();
+            if i >= self.entries.len() {
+                genome.mutate(rng, innovations);
+            }
+            genome
+        }).collect()
+    }
+
+    // Each member is written as `entry <generation> <species id> <fitness>` followed by its genome
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = try!(File::create(path));
+        for entry in self.entries.iter() {
+            try!(writeln!(file, "entry {} {} {}", entry.generation, entry.species_id, entry.fitness));
+            try!(entry.genome.write(&mut file));
+        }
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<HallOfFame> {
+        let mut text = String::new();
+        try!(try!(File::open(path)).read_to_string(&mut text));
+
+        let mut hall_of_fame = HallOfFame::new(capacity);
+        for chunk in text.split("entry ").filter(|chunk| chunk.trim().len() > 0) {
+            let mut lines = chunk.splitn(2, '\n');
+            let meta: Vec<&str> = lines.next().unwrap_or("").split_whitespace().collect();
+            if meta.len() != 3 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Bad hall of fame entry"));
+            }
+            let generation = try!(meta[0].parse().map_err(|_| bad_entry()));
+            let species_id = try!(meta[1].parse().map_err(|_| bad_entry()));
+            let fitness = try!(meta[2].parse().map_err(|_| bad_entry()));
+            let genome = try!(Genome::parse(lines.next().unwrap_or("")));
+            hall_of_fame.consider(&genome, fitness, generation, species_id);
+        }
+        Ok(hall_of_fame)
+    }
+}
+
+fn bad_entry() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Bad hall of fame entry")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat;
+    use neat::genetics::{Gene, Genome, Innovations};
+
+    fn genome(weight: f64) -> Genome {
+        Genome::new(vec![
+            Gene{ into: 0, out: 2, weight: weight, enabled: true, innovation: 1 }
+        ], 2, 1)
+    }
+
+    #[test]
+    fn hall_of_fame_keeps_best_distinct() {
+        let mut hall_of_fame = HallOfFame::new(2);
+        assert!(hall_of_fame.consider(&genome(0.1), -3.0, 1, 1));
+        assert!(hall_of_fame.consider(&genome(0.2), -1.0, 1, 2));
+        assert!(!hall_of_fame.consider(&genome(0.2), -2.0, 2, 2));
+        assert!(hall_of_fame.consider(&genome(0.3), -2.0, 2, 3));
+
+        assert!(hall_of_fame.len() == 2);
+        assert!(hall_of_fame.best().unwrap().fitness == -1.0);
+        assert!(hall_of_fame.entries()[1].species_id == 3);
+        assert!(hall_of_fame.seed(5, &mut neat::seeded_rng(1), &mut Innovations::new()).len() == 5);
+    }
+}
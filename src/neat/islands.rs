@@ -0,0 +1,203 @@
+// Island model: several populations evolving side by side, each with its own `Creator` and
+// settings, that swap their best genomes every few generations. Every island's `Creator` numbers
+// new genes from the archipelago's one innovation counter, so migrants line up with the locals
+// they cross with.
+
+use neat;
+use neat::genetics::{Genome, Innovations};
+use neat::population::Population;
+use neat::stats::GenerationStats;
+use neat::task::Task;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Topology {
+    // Each island sends migrants to the next one
+    Ring,
+    // Each island sends migrants to every other one
+    FullyConnected
+}
+
+pub struct Archipelago {
+    pub islands: Vec<Population>,
+    // Handed to every island's `Creator`
+    pub innovations: Innovations,
+    topology: Topology,
+    // Generations between migrations
+    interval: u64,
+    // Genomes each island sends along every route
+    migrants: usize,
+    generation: u64
+}
+
+impl Archipelago {
+    pub fn new(mut islands: Vec<Population>, topology: Topology, interval: u64, migrants: usize)
+            -> Archipelago {
+        // Numbers any island has already given out stay taken
+        let taken = islands.iter().map(|island| island.creator.innovations.current()).max();
+        let innovations = Innovations::starting_at(taken.unwrap_or(0));
+        for island in islands.iter_mut() {
+            island.creator.innovations = innovations.clone();
+        }
+
+        Archipelago {
+            islands: islands,
+            innovations: innovations,
+            topology: topology,
+            interval: interval,
+            migrants: migrants,
+            generation: 0
+        }
+    }
+
+    // Evaluate every island, migrate if it's time, then let every island reproduce
+    pub fn step<T: Task>(&mut self, task: &mut T) -> Vec<GenerationStats> {
+        for island in self.islands.iter_mut() {
+            island.evaluate(task);
+        }
+
+        self.generation += 1;
+        if self.interval > 0 && self.generation % self.interval == 0 {
+            self.migrate();
+        }
+
+        self.islands.iter_mut().map(|island| island.reproduce()).collect()
+    }
+
+    pub fn run<T: Task>(&mut self, task: &mut T, generations: u64) -> Option<&Genome> {
+        for _ in 0..generations {
+            self.step(task);
+        }
+        self.champion()
+    }
+
+    // Best genome any island has evaluated
+    pub fn champion(&self) -> Option<&Genome> {
+        self.islands.iter()
+            .filter_map(|island| island.champion.as_ref())
+            .fold(None, |best: Option<&Genome>, genome| match best {
+                Some(b) if b.fitness >= genome.fitness => Some(b),
+                _ => Some(genome)
+            })
+    }
+
+    // Copies of each island's best evaluated genomes replace the worst ones of its destinations,
+    // genomes with NaN fitness count as the worst
+    pub fn migrate(&mut self) {
+        let n = self.islands.len();
+        let mut arrivals: Vec<Vec<Genome>> = (0..n).map(|_| Vec::new()).collect();
+
+        for (i, island) in self.islands.iter().enumerate() {
+            let mut best: Vec<&Genome> = island.genomes.iter().collect();
+            best.sort_by(|a, b| neat::compare(b.fitness, a.fitness));
+            best.truncate(self.migrants);
+
+            for destination in self.destinations(i) {
+                arrivals[destination].extend(best.iter().map(|genome| Genome::clone(genome)));
+            }
+        }
+
+        for (island, migrants) in self.islands.iter_mut().zip(arrivals.into_iter()) {
+            island.genomes.sort_by(|a, b| neat::compare(a.fitness, b.fitness));
+            for (local, migrant) in island.genomes.iter_mut().zip(migrants.into_iter()) {
+                *local = migrant;
+            }
+        }
+    }
+
+    fn destinations(&self, source: usize) -> Vec<usize> {
+        let n = self.islands.len();
+        match self.topology {
+            Topology::Ring if n > 1 => vec![(source + 1) % n],
+            Topology::Ring => Vec::new(),
+            Topology::FullyConnected => (0..n).filter(|&i| i != source).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat::Creator;
+    use neat::benchmarks::Xor;
+    use neat::config::Config;
+    use neat::genetics::Genome;
+    use neat::population::Population;
+
+    fn islands(n: usize) -> Vec<Population> {
+        (0..n).map(|i| {
+            let mut config = Config::new();
+            config.seed = Some(i);
+            Population::new(&Xor, 10, Creator::with_config(config))
+        }).collect()
+    }
+
+    #[test]
+    fn migrants_replace_worst_in_ring() {
+        let mut archipelago = Archipelago::new(islands(3), Topology::Ring, 1, 2);
+        for (i, island) in archipelago.islands.iter_mut().enumerate() {
+            for (j, genome) in island.genomes.iter_mut().enumerate() {
+                genome.fitness = (i * 100 + j) as f64;
+            }
+        }
+        let best: Genome = archipelago.islands[0].genomes[9].clone();
+
+        archipelago.migrate();
+
+        let hash = best.structural_hash();
+        assert!(archipelago.islands[1].genomes.iter().any(|genome|
+            genome.fitness == 9.0 && genome.structural_hash() == hash));
+        assert!(archipelago.islands[1].genomes.iter().all(|genome| genome.fitness != 100.0));
+        assert!(archipelago.islands.iter().all(|island| island.genomes.len() == 10));
+    }
+
+    #[test]
+    fn nan_fitness_never_migrates() {
+        let mut archipelago = Archipelago::new(islands(2), Topology::Ring, 1, 2);
+        for (i, island) in archipelago.islands.iter_mut().enumerate() {
+            for (j, genome) in island.genomes.iter_mut().enumerate() {
+                genome.fitness = if j % 3 == 0 { f64::NAN } else { (i * 100 + j) as f64 };
+            }
+        }
+
+        archipelago.migrate();
+
+        // Island 0's best two took the place of island 1's NaNs and the NaNs stayed home
+        let fitness: Vec<f64> = archipelago.islands[1].genomes.iter().map(|genome| genome.fitness).collect();
+        assert!(fitness.contains(&8.0) && fitness.contains(&7.0));
+        assert!(fitness.iter().filter(|f| f.is_nan()).count() == 2);
+        assert!(archipelago.islands[0].genomes.iter().filter(|genome| genome.fitness.is_nan()).count() == 2);
+    }
+
+    #[test]
+    fn islands_never_reuse_innovation_numbers() {
+        let mut archipelago = Archipelago::new(islands(2), Topology::Ring, 1, 1);
+        for (i, island) in archipelago.islands.iter_mut().enumerate() {
+            for (j, genome) in island.genomes.iter_mut().enumerate() {
+                genome.fitness = (i * 100 + j) as f64;
+            }
+        }
+        archipelago.migrate();
+
+        // The migrant and a local each grow a new node on their own island
+        let mut added = Vec::new();
+        for (island, fitness) in archipelago.islands.iter_mut().zip([109.0, 105.0].iter()) {
+            let creator = &mut island.creator;
+            let genome = island.genomes.iter_mut().find(|genome| genome.fitness == *fitness).unwrap();
+            let before = genome.genes.len();
+            genome.mutate_node(&mut creator.rng, &mut creator.innovations);
+            added.push(genome.genes[before..].iter().map(|gene| gene.innovation).collect::<Vec<_>>());
+        }
+
+        assert!(added[0].len() == 2 && added[1].len() == 2);
+        assert!(added[0].iter().all(|innovation| !added[1].contains(innovation)));
+        assert!(archipelago.innovations.current() >= 4);
+    }
+
+    #[test]
+    fn archipelago_runs_task() {
+        let mut archipelago = Archipelago::new(islands(3), Topology::FullyConnected, 2, 1);
+        assert!(archipelago.run(&mut Xor, 4).is_some());
+        assert!(archipelago.islands.iter().all(|island|
+            island.genomes.len() == 10 && island.creator.generation() == 4));
+    }
+}
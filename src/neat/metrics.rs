@@ -0,0 +1,130 @@
+use neat::genetics::Genome;
+use neat::stats::GenerationStats;
+
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const CSV_HEADER: &'static str = "generation,min_fitness,mean_fitness,max_fitness,stddev_fitness,\
+num_species,species_sizes,mean_genes,mean_hidden_nodes,compatibility_threshold,innovation";
+
+// Appends a CSV row and a JSON object per generation, and optionally dumps the champion genome
+// every few generations.
+pub struct Recorder {
+    csv: File,
+    jsonl: File,
+    champion_dir: Option<PathBuf>,
+    champion_every: u64
+}
+
+impl Recorder {
+    pub fn new<P: AsRef<Path>, Q: AsRef<Path>>(csv_path: P, jsonl_path: Q) -> io::Result<Recorder> {
+        let write_header = !csv_path.as_ref().exists();
+        let mut csv = try!(OpenOptions::new().create(true).append(true).open(csv_path));
+        if write_header {
+            try!(writeln!(csv, "{}", CSV_HEADER));
+        }
+
+        let jsonl = try!(OpenOptions::new().create(true).append(true).open(jsonl_path));
+
+        Ok(Recorder {
+            csv: csv,
+            jsonl: jsonl,
+            champion_dir: None,
+            champion_every: 0
+        })
+    }
+
+    pub fn dump_champions<P: AsRef<Path>>(&mut self, dir: P, every: u64) -> io::Result<()> {
+        try!(fs::create_dir_all(dir.as_ref()));
+        self.champion_dir = Some(dir.as_ref().to_path_buf());
+        self.champion_every = every;
+        Ok(())
+    }
+
+    pub fn record(&mut self, stats: &GenerationStats, champion: &Genome) -> io::Result<()> {
+        try!(writeln!(self.csv, "{}", csv_row(stats)));
+        try!(writeln!(self.jsonl, "{}", json_object(stats)));
+
+        if let Some(ref dir) = self.champion_dir {
+            if self.champion_every > 0 && stats.generation % self.champion_every == 0 {
+                let path = dir.join(format!("champion-{:05}.genome", stats.generation));
+                let mut file = try!(File::create(path));
+                try!(champion.write(&mut file));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn csv_row(stats: &GenerationStats) -> String {
+    let sizes: Vec<String> = stats.species.iter().map(|spec| spec.size.to_string()).collect();
+    format!("{},{},{},{},{},{},{},{},{},{},{}",
+            stats.generation, stats.min_fitness, stats.mean_fitness, stats.max_fitness,
+            stats.stddev_fitness, stats.species.len(), sizes.join(";"), stats.mean_genes,
+            stats.mean_hidden_nodes, stats.compatibility_threshold, stats.innovation)
+}
+
+fn json_object(stats: &GenerationStats) -> String {
+    let species: Vec<String> = stats.species.iter().map(|spec|
+        format!("{{\"id\":{},\"size\":{},\"average_fitness\":{},\"best_fitness\":{},\"staleness\":{}}}",
+                spec.id, spec.size, json_number(spec.average_fitness),
+                json_number(spec.best_fitness), spec.staleness)
+    ).collect();
+
+    format!("{{\"generation\":{},\
+             \"fitness\":{{\"min\":{},\"mean\":{},\"max\":{},\"stddev\":{}}},\
+             \"species\":[{}],\
+             \"complexity\":{{\"mean_genes\":{},\"mean_hidden_nodes\":{}}},\
+             \"compatibility_threshold\":{},\"innovation\":{}}}",
+            stats.generation, json_number(stats.min_fitness), json_number(stats.mean_fitness),
+            json_number(stats.max_fitness), json_number(stats.stddev_fitness), species.join(","),
+            json_number(stats.mean_genes), json_number(stats.mean_hidden_nodes),
+            json_number(stats.compatibility_threshold), stats.innovation)
+}
+
+// JSON has no NaN or infinity
+fn json_number(x: f64) -> String {
+    if x.is_finite() { x.to_string() } else { "null".to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv_row, json_object, CSV_HEADER};
+    use neat::stats::{GenerationStats, SpeciesStats};
+
+    fn stats() -> GenerationStats {
+        GenerationStats {
+            generation: 3,
+            min_fitness: -2.0,
+            mean_fitness: -1.5,
+            max_fitness: -1.0,
+            stddev_fitness: 0.5,
+            species: vec![
+                SpeciesStats { id: 1, size: 2, average_fitness: -1.5, best_fitness: -1.0, staleness: 0 },
+                SpeciesStats { id: 4, size: 1, average_fitness: -2.0, best_fitness: -1.0, staleness: 2 }
+            ],
+            mean_genes: 3.0,
+            mean_hidden_nodes: 1.0,
+            compatibility_threshold: 1.0,
+            innovation: 12
+        }
+    }
+
+    #[test]
+    fn csv_rows_match_header() {
+        let row = csv_row(&stats());
+        assert!(row.split(',').count() == CSV_HEADER.split(',').count());
+        assert!(row.starts_with("3,-2,-1.5,-1,0.5,2,2;1,"));
+    }
+
+    #[test]
+    fn json_objects_are_single_lines() {
+        let json = json_object(&stats());
+        assert!(!json.contains('\n'));
+        assert!(json.starts_with("{\"generation\":3,\"fitness\":{\"min\":-2,"));
+        assert!(json.contains("{\"id\":4,\"size\":1,\"average_fitness\":-2,\"best_fitness\":-1,\"staleness\":2}"));
+    }
+}
@@ -1,94 +1,494 @@
+pub mod benchmarks;
+pub mod checkpoint;
+pub mod config;
+pub mod control;
+pub mod distributed;
 pub mod genetics;
+pub mod hall_of_fame;
+pub mod islands;
+pub mod metrics;
 pub mod neurology;
+pub mod novelty;
+pub mod pareto;
+pub mod population;
+pub mod selection;
+pub mod stats;
+pub mod task;
 pub mod taxonomy;
 
-use neat::genetics::Genome;
-use neat::taxonomy::Species;
+use neat::config::{Config, Objective};
+use neat::genetics::{Genome, Innovations};
+use neat::hall_of_fame::HallOfFame;
+use neat::metrics::Recorder;
+use neat::novelty::NoveltyArchive;
+use neat::stats::{GenerationStats, SpeciesStats};
+use neat::taxonomy::{Species, DIFFERENCE_THRESHOLD};
 
-use rand;
-use rand::{SeedableRng, StdRng, ThreadRng};
-static RNG_SEED: &'static[usize] = &[294367];
+use rand::{Rng, SeedableRng, StdRng};
 
-#[cfg(test)]
-pub fn rng() -> StdRng {
-    SeedableRng::from_seed(RNG_SEED)
+use std::cmp::Ordering;
+use std::f64;
+
+// Best genomes of each generation a judge scores for the hall of fame
+const JUDGED: usize = 3;
+
+// Random numbers for a run, the same seed always gives the same numbers
+pub fn seeded_rng(seed: usize) -> StdRng {
+    SeedableRng::from_seed(&[seed][..])
 }
 
-#[cfg(not(test))]
-pub fn rng() -> ThreadRng {
-    rand::thread_rng()
+// Seeded from the operating system, for runs that don't need repeating
+pub fn entropy_rng() -> StdRng {
+    StdRng::new().expect("No OS randomness")
+}
+
+// Orders scores with NaN below every number, so sorting never panics and a NaN score is never
+// taken for a good one
+pub fn compare(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.partial_cmp(&b).unwrap()
+    }
 }
 
 pub struct Creator {
-    species: Vec<Species>
+    species: Vec<Species>,
+    config: Config,
+    generation: u64,
+    next_species_id: u64,
+    // Species id of every genome of the population last sorted into species, in population order
+    members: Vec<u64>,
+    recorder: Option<Recorder>,
+    // Scores hall of fame candidates in place of their fitness when set
+    judge: Option<Box<FnMut(&Genome) -> f64>>,
+    // Every random choice of the run comes from here, seeded from the config
+    pub rng: StdRng,
+    pub innovations: Innovations,
+    pub archive: NoveltyArchive,
+    pub hall_of_fame: HallOfFame,
+    pub history: Vec<GenerationStats>
 }
 
 impl Creator {
     pub fn new() -> Creator {
+        Creator::with_config(Config::new())
+    }
+
+    pub fn with_config(config: Config) -> Creator {
+        let archive = NoveltyArchive::new(config.novelty_neighbours, config.novelty_threshold);
+        let hall_of_fame = HallOfFame::new(config.hall_of_fame_size);
+        let rng = match config.seed {
+            Some(seed) => seeded_rng(seed),
+            None => entropy_rng()
+        };
         Creator {
-            species: Vec::new()
+            species: Vec::new(),
+            archive: archive,
+            hall_of_fame: hall_of_fame,
+            config: config,
+            generation: 0,
+            next_species_id: 1,
+            members: Vec::new(),
+            recorder: None,
+            judge: None,
+            rng: rng,
+            innovations: Innovations::new(),
+            history: Vec::new()
         }
     }
-    pub fn next_generation(&mut self, genomes: Vec<&Genome>) -> Vec<Genome> {
-        info!("Best fitness: {:?}", genomes.iter().fold(genomes[0],
-            |a, b| if a.fitness > b.fitness { a } else { b } ));
 
-        // Classify genomes
-        for genome in genomes.iter() {
-            self.add_genome((*genome).clone());
+    // Starting genomes for a run
+    pub fn random_genomes(&mut self, size: usize, num_inputs: u64, num_outputs: u64) -> Vec<Genome> {
+        let rng = &mut self.rng;
+        (0..size).map(|_| Genome::random(num_inputs, num_outputs, rng)).collect()
+    }
+
+    pub fn set_recorder(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    // Only the best few genomes of every generation get into the hall of fame from now on, with
+    // the score `judge` gives them. For fitness that depends on who else was around, like
+    // opponents in coevolution, so hall of fame scores from different generations compare. The
+    // judge is called `JUDGED` times a generation, its cost adds to every generation's.
+    pub fn set_judge(&mut self, judge: Box<FnMut(&Genome) -> f64>) {
+        self.judge = Some(judge);
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn next_generation(&mut self, genomes: Vec<&Genome>) -> (Vec<Genome>, GenerationStats) {
+        let (stats, champion) = self.assess(&genomes);
+
+        // The whole generation retires
+        if self.uses_novelty() {
+            self.archive.score(&genomes);
         }
+        self.members.clear();
 
-        // Kill the weak
+        // Kill the weak and elect representatives
         for spec in self.species.iter_mut() {
-            spec.calc_average_fitness();
             spec.cull();
+            spec.assign_representative(&mut self.rng);
+        }
+
+        // Reproduce based on adjusted fitness
+        let num_offspring = genomes.len();
+        let shares: Vec<f64> = self.species.iter().map(|s| s.adjusted_fitness()).collect();
+        let allocation = allocate_offspring(&shares, num_offspring);
+        let mut offspring: Vec<Genome> = Vec::with_capacity(num_offspring);
+        for (i, &num_spec_offspring) in allocation.iter().enumerate() {
+            for _ in 0..num_spec_offspring {
+                offspring.push(self.breed_child(i));
+            }
+        }
+
+        let stats = self.finish(stats, genomes[champion]);
+        info!("Next generation has {} species", self.species.len());
+        return (offspring, stats);
+    }
+
+    // Steady state reproduction as in rtNEAT. The eligible genome with the lowest fitness shared
+    // with its species makes room for a child of a species picked by adjusted fitness. Species
+    // carry over between calls so the rest of the population is left alone, only the replaced
+    // genome leaves its species and the child joins one. A population that isn't the one the
+    // species last saw, with the child in place, is sorted into species from scratch. Returns the
+    // index of the genome to replace and the child replacing it.
+    pub fn replace_worst(&mut self, genomes: &[&Genome], eligible: &[bool]) -> Option<(usize, Genome)> {
+        if !eligible.iter().any(|&e| e) {
+            return None;
+        }
+
+        let objective = objective_fitness(genomes);
+        let members = if self.holds(genomes) {
+            self.rescore(genomes, &objective)
+        } else {
+            self.speciate(genomes, &objective)
+        };
+        let min_fitness = members.iter().fold(f64::INFINITY, |acc, &(_, fitness)| acc.min(fitness));
+        for spec in self.species.iter_mut() {
+            spec.calc_average_fitness();
+            spec.calc_adjusted_fitness(-min_fitness);
+        }
+
+        // Big species pay for their size, same as in fitness sharing
+        let sizes: Vec<usize> = members.iter()
+            .map(|&(id, _)| members.iter().filter(|&&(other, _)| other == id).count())
+            .collect();
+        let shared = |i: usize| (members[i].1 - min_fitness) / sizes[i] as f64;
+        let worst = (0..genomes.len()).filter(|&i| eligible[i])
+            .fold(None, |worst: Option<usize>, i| match worst {
+                Some(w) if shared(w) <= shared(i) => Some(w),
+                _ => Some(i)
+            })
+            .unwrap();
+        let (worst_id, _) = members[worst];
+        if self.judge.is_none() {
+            let fitness = self.headline_fitness(&genomes[worst..worst + 1])[0];
+            self.hall_of_fame.consider(genomes[worst], fitness, self.generation, worst_id);
+        }
+        if self.uses_novelty() {
+            let behaviors: Vec<&Vec<f64>> = genomes.iter().map(|genome| &genome.behavior).collect();
+            let novelty = self.archive.sparseness(worst, &behaviors);
+            self.archive.retire(genomes[worst], novelty);
         }
 
+        // Species hold their members in population order
+        let place = members[..worst].iter().filter(|&&(id, _)| id == worst_id).count();
+        if let Some(spec) = self.species.iter_mut().find(|spec| spec.id == worst_id) {
+            spec.genomes.remove(place);
+            if spec.genomes.len() > 0 {
+                spec.calc_average_fitness();
+                spec.calc_adjusted_fitness(-min_fitness);
+            }
+        }
+        self.species.retain(|spec| spec.genomes.len() > 0);
+        if self.species.len() == 0 {
+            // Nobody left to breed from, everyone gets sorted again next time
+            self.members.clear();
+            return None;
+        }
+
+        for spec in self.species.iter_mut() {
+            spec.assign_representative(&mut self.rng);
+        }
+        let shares: Vec<f64> = self.species.iter().map(|s| s.adjusted_fitness()).collect();
+        let parent = selection::spin(&shares, &mut self.rng);
+        let child = self.breed_child(parent);
+
+        // The child takes the replaced genome's place in the population and in its new species
+        let id = self.add_genome(child.clone());
+        let place = self.members[..worst].iter().filter(|&&other| other == id).count();
+        if let Some(spec) = self.species.iter_mut().find(|spec| spec.id == id) {
+            let newborn = spec.genomes.pop().unwrap();
+            spec.genomes.insert(place, newborn);
+        }
+        self.members[worst] = id;
+        Some((worst, child))
+    }
+
+    // Stats of a population that's replaced a genome at a time, counted as a generation
+    pub fn report(&mut self, genomes: Vec<&Genome>) -> GenerationStats {
+        let (stats, champion) = self.assess(&genomes);
+        self.finish(stats, genomes[champion])
+    }
+
+    // Sort the genomes into species and score them, returns the stats so far and the champion
+    fn assess(&mut self, genomes: &[&Genome]) -> (GenerationStats, usize) {
+        self.generation += 1;
+        let headline = self.headline_fitness(genomes);
+        let mut stats = GenerationStats::new(self.generation, genomes, &headline);
+
+        let champion = (0..genomes.len()).fold(0,
+            |a, b| if headline[a] >= headline[b] { a } else { b } );
+        info!("Best fitness: {:?}", genomes[champion]);
+
+        let objective = objective_fitness(genomes);
+        let members = self.speciate(genomes, &objective);
+        match self.judge {
+            Some(ref mut judge) => {
+                let mut best: Vec<usize> = (0..genomes.len()).collect();
+                best.sort_by(|&a, &b| compare(headline[b], headline[a]));
+                for &i in best.iter().take(JUDGED) {
+                    self.hall_of_fame.consider(genomes[i], judge(genomes[i]), self.generation, members[i].0);
+                }
+            },
+            None => {
+                for ((genome, &fitness), &(species_id, _)) in genomes.iter().zip(headline.iter()).zip(members.iter()) {
+                    self.hall_of_fame.consider(genome, fitness, self.generation, species_id);
+                }
+            }
+        }
+
+        // Share fitness within species, shifted by the worst genome's fitness so it can be negative
+        let min_fitness = members.iter().fold(members[0].1, |acc, &(_, fitness)| acc.min(fitness));
+        for spec in self.species.iter_mut() {
+            spec.calc_average_fitness();
+            spec.calc_adjusted_fitness(-min_fitness);
+
+            // Progress is judged on the headline fitness, novelty changes as the archive fills up
+            // and Pareto ranks are relative to the generation
+            let spec_objective: Vec<f64> = members.iter().zip(headline.iter())
+                .filter(|&(&(id, _), _)| id == spec.id)
+                .map(|(_, &fitness)| fitness)
+                .collect();
+            let best = spec_objective.iter().fold(f64::NEG_INFINITY, |acc, &f| acc.max(f));
+            spec.calc_staleness(best);
+            stats.species.push(SpeciesStats::new(spec, &spec_objective));
+        }
+
+        (stats, champion)
+    }
+
+    // Put the genomes into species, returns the species id and scored fitness of each
+    fn speciate(&mut self, genomes: &[&Genome], objective: &[f64]) -> Vec<(u64, f64)> {
+        // Previous genomes are gone, only the representatives carry over
+        for spec in self.species.iter_mut() {
+            spec.genomes.clear();
+        }
+
+        // Classify genomes by what they'll be selected on
+        let scored = self.score(genomes, objective);
+        let members: Vec<(u64, f64)> = scored.into_iter().map(|genome| {
+            let fitness = genome.fitness;
+            (self.add_genome(genome), fitness)
+        }).collect();
+
         // Remove species with no genomes
         self.species.retain(|spec| spec.genomes.len() > 0);
+        self.members = members.iter().map(|&(id, _)| id).collect();
+        members
+    }
+
+    // Whether the species hold exactly `genomes`, each in the species `members` says, in
+    // population order
+    fn holds(&self, genomes: &[&Genome]) -> bool {
+        if self.members.len() != genomes.len() {
+            return false;
+        }
+        self.species.iter().all(|spec| {
+            let population = self.members.iter().zip(genomes.iter())
+                .filter(|&(&member, _)| member == spec.id)
+                .map(|(_, genome)| genome);
+            spec.genomes.len() == population.clone().count() &&
+                spec.genomes.iter().zip(population).all(|(held, genome)| held.genes == genome.genes)
+        })
+    }
+
+    // Score the genomes again in the species they're already in, returns the same as `speciate`
+    fn rescore(&mut self, genomes: &[&Genome], objective: &[f64]) -> Vec<(u64, f64)> {
+        let scored: Vec<f64> = self.score(genomes, objective).iter().map(|genome| genome.fitness).collect();
 
-        // Elect representatives
+        // Species hold their members in population order
         for spec in self.species.iter_mut() {
-            spec.assign_representative();
+            let id = spec.id;
+            let fitness = self.members.iter().zip(scored.iter())
+                .filter(|&(&member, _)| member == id)
+                .map(|(_, &fitness)| fitness);
+            for (genome, fitness) in spec.genomes.iter_mut().zip(fitness) {
+                genome.fitness = fitness;
+            }
         }
+        self.members.iter().cloned().zip(scored.into_iter()).collect()
+    }
 
-        // Reproduce based on fitness
-        let num_offspring = genomes.len();
-        let mut offspring: Vec<Genome> = Vec::with_capacity(num_offspring);
-        let total_avg_fitness = self.species.iter().fold(0f64, |acc, s| s.average_fitness() + acc);
-        for spec in self.species.iter() {
-            let fitness_percentage = spec.average_fitness() / total_avg_fitness;
-            let num_spec_offspring = fitness_percentage * num_offspring as f64;
-            for _ in 0..(num_spec_offspring as usize) {
-                offspring.push(spec.breed_child());
+    fn finish(&mut self, stats: GenerationStats, champion: &Genome) -> GenerationStats {
+        let mut stats = stats;
+        stats.compatibility_threshold = DIFFERENCE_THRESHOLD;
+        stats.innovation = self.innovations.current();
+        self.history.push(stats.clone());
+
+        if let Some(ref mut recorder) = self.recorder {
+            if let Err(e) = recorder.record(&stats, champion) {
+                error!("Failed to record generation {}: {}", stats.generation, e);
             }
         }
+        stats
+    }
 
-        info!("Next generation has {} species", self.species.len());
-        return offspring;
+    // What genomes are reported and remembered by: their fitness, or the primary objective when
+    // they carry objective scores
+    fn headline_fitness(&self, genomes: &[&Genome]) -> Vec<f64> {
+        let primary = self.config.primary_objective;
+        genomes.iter().map(|genome| match genome.objectives.get(primary) {
+            Some(&score) => score,
+            None => genome.fitness
+        }).collect()
+    }
+
+    // Weight of novelty in what genomes are selected on
+    fn novelty_weight(&self) -> f64 {
+        match self.config.objective {
+            Objective::Fitness => 0f64,
+            Objective::Novelty => 1f64,
+            Objective::Blend(weight) => weight
+        }
+    }
+
+    fn uses_novelty(&self) -> bool {
+        self.novelty_weight() != 0f64
     }
 
-    fn add_genome(&mut self, genome: Genome) {
+    // Copies of the genomes with fitness replaced by the configured objective. Novelty is measured
+    // against the archive as it is, genomes only join it once they retire.
+    fn score(&self, genomes: &[&Genome], objective: &[f64]) -> Vec<Genome> {
+        let mut scored: Vec<Genome> = genomes.iter().map(|genome| (*genome).clone()).collect();
+
+        let weight = self.novelty_weight();
+        if weight == 0f64 {
+            for (genome, &fitness) in scored.iter_mut().zip(objective.iter()) {
+                genome.fitness = fitness;
+            }
+            return scored;
+        }
+
+        let novelty = normalize(&self.archive.novelty(genomes));
+        let fitness = normalize(objective);
+        for (i, genome) in scored.iter_mut().enumerate() {
+            genome.fitness = weight * novelty[i] + (1f64 - weight) * fitness[i];
+        }
+
+        scored
+    }
+
+    fn breed_child(&mut self, spec_idx: usize) -> Genome {
+        let spec = &self.species[spec_idx];
+        let selection = &*self.config.selection;
+        let rng = &mut self.rng;
+        let innovations = &mut self.innovations;
+
+        if self.species.len() > 1 && rng.gen::<f64>() < self.config.interspecies_rate {
+            let mut other_idx = rng.gen_range(0, self.species.len() - 1);
+            if other_idx >= spec_idx {
+                other_idx += 1;
+            }
+            let mate = self.species[other_idx].select_parent(selection, rng);
+            return spec.breed_child_with(mate, selection, rng, innovations);
+        }
+
+        spec.breed_child(selection, rng, innovations)
+    }
+
+    // Puts the genome in the first compatible species, returns the species id
+    fn add_genome(&mut self, genome: Genome) -> u64 {
         for spec in self.species.iter_mut() {
             if spec.compatible(&genome) {
                 spec.add_genome(genome);
-                return;
+                return spec.id;
             }
         }
 
-        let spec = Species {
-            representative: genome.clone(),
-            genomes: vec![genome.clone()],
-            avg_fitness: 0f64
-        };
-        self.species.push(spec);
+        let id = self.next_species_id;
+        self.species.push(Species::new(id, genome));
+        self.next_species_id += 1;
+        id
     }
 }
 
+// Fitness from Pareto ranking when every genome carries objective scores, plain fitness otherwise
+fn objective_fitness(genomes: &[&Genome]) -> Vec<f64> {
+    if genomes.iter().all(|genome| genome.objectives.len() > 0) {
+        let points: Vec<&Vec<f64>> = genomes.iter().map(|genome| &genome.objectives).collect();
+        return pareto::pareto_fitness(&points);
+    }
+    genomes.iter().map(|genome| genome.fitness).collect()
+}
+
+// Scale values to 0..1, all zeros if they are all equal
+fn normalize(values: &[f64]) -> Vec<f64> {
+    let min = values.iter().fold(f64::INFINITY, |acc, &x| acc.min(x));
+    let max = values.iter().fold(f64::NEG_INFINITY, |acc, &x| acc.max(x));
+    values.iter().map(|&x| if max > min { (x - min) / (max - min) } else { 0f64 }).collect()
+}
+
+// Split `total` offspring proportionally to `shares` using largest remainder rounding, so the
+// allocation always sums to exactly `total`. Equal shares are used when every share is zero.
+fn allocate_offspring(shares: &[f64], total: usize) -> Vec<usize> {
+    if shares.len() == 0 {
+        return Vec::new();
+    }
+
+    let sum = shares.iter().fold(0f64, |acc, &share| acc + share);
+    let quotas: Vec<f64> = shares.iter().map(|&share|
+        if sum > 0f64 {
+            share / sum * total as f64
+        } else {
+            total as f64 / shares.len() as f64
+        }
+    ).collect();
+
+    let mut allocation: Vec<usize> = quotas.iter().map(|quota| quota.floor() as usize).collect();
+    let allocated = allocation.iter().fold(0, |acc, &n| acc + n);
+
+    // Hand out the remaining seats to the largest fractional parts, NaN quotas from infinite
+    // shares go last
+    let mut remainders: Vec<(usize, f64)> = quotas.iter().enumerate()
+        .map(|(i, quota)| (i, quota - quota.floor()))
+        .collect();
+    remainders.sort_by(|a, b| compare(a.1, b.1).reverse());
+    for &(i, _) in remainders.iter().cycle().take(total - allocated) {
+        allocation[i] += 1;
+    }
+
+    allocation
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use neat::config::{Config, Objective};
     use neat::genetics::{Gene, Genome};
+    use neat::selection::Uniform;
+
+    fn seeded_config() -> Config {
+        let mut config = Config::new();
+        config.seed = Some(1);
+        config
+    }
 
     #[test]
     fn creator_maintains_population() {
@@ -107,9 +507,184 @@ mod tests {
         genome1.fitness = -2.0;
         genome2.fitness = -2.0;
 
-        let mut creator = Creator::new();
-        let next_gen = creator.next_generation(vec![&genome1, &genome2]);
+        let mut creator = Creator::with_config(seeded_config());
+        let (next_gen, stats) = creator.next_generation(vec![&genome1, &genome2]);
 
         assert!(next_gen.len() == 2);
+        assert!(stats.species.iter().fold(0, |acc, spec| acc + spec.size) == 2);
+    }
+
+    #[test]
+    fn creator_maintains_population_over_generations() {
+        let mut creator = Creator::with_config(seeded_config());
+        let mut genomes = creator.random_genomes(20, 2, 1);
+
+        for _ in 0..50 {
+            for (i, genome) in genomes.iter_mut().enumerate() {
+                genome.fitness = -(i as f64) - genome.genes.len() as f64;
+            }
+            genomes = creator.next_generation(genomes.iter().collect()).0;
+            assert!(genomes.len() == 20);
+        }
+        assert!(creator.history.len() == 50);
+        assert!(creator.hall_of_fame.len() > 0);
+    }
+
+    #[test]
+    fn creator_mates_across_species() {
+        let mut config = seeded_config();
+        config.selection = Box::new(Uniform);
+        config.interspecies_rate = 1.0;
+        let mut creator = Creator::with_config(config);
+        let mut genomes = creator.random_genomes(10, 2, 1);
+
+        for _ in 0..5 {
+            genomes = creator.next_generation(genomes.iter().collect()).0;
+            assert!(genomes.len() == 10);
+        }
+    }
+
+    #[test]
+    fn creator_selects_on_novelty() {
+        let mut config = seeded_config();
+        config.objective = Objective::Blend(0.5);
+        config.novelty_threshold = 1.0;
+        let mut creator = Creator::with_config(config);
+        let mut genomes = creator.random_genomes(10, 2, 1);
+
+        for generation in 0..5 {
+            for (i, genome) in genomes.iter_mut().enumerate() {
+                genome.fitness = -(i as f64) - 1f64;
+                genome.behavior = vec![i as f64, generation as f64];
+            }
+            let (next, stats) = creator.next_generation(genomes.iter().collect());
+            genomes = next;
+            assert!(genomes.len() == 10);
+
+            // Species are reported on fitness, not the blend they are selected on
+            for spec in stats.species.iter() {
+                assert!(spec.average_fitness <= -1.0 && spec.best_fitness <= -1.0);
+            }
+        }
+        assert!(creator.archive.behaviors.len() > 0);
+    }
+
+    #[test]
+    fn creator_ranks_multiple_objectives() {
+        let mut creator = Creator::with_config(seeded_config());
+        let mut genomes = creator.random_genomes(10, 2, 1);
+
+        for _ in 0..5 {
+            for (i, genome) in genomes.iter_mut().enumerate() {
+                genome.objectives = vec![i as f64, -(genome.genes.len() as f64)];
+            }
+            let (next, stats) = creator.next_generation(genomes.iter().collect());
+            genomes = next;
+            assert!(genomes.len() == 10);
+
+            // Stats and the hall of fame go by the first objective
+            assert!(stats.max_fitness == 9.0 && stats.min_fitness == 0.0);
+            assert!(creator.hall_of_fame.best().unwrap().fitness == 9.0);
+        }
+    }
+
+    #[test]
+    fn creator_replaces_worst_eligible_genome() {
+        let mut creator = Creator::with_config(seeded_config());
+        let mut genomes: Vec<Genome> = (0..4).map(|_| Genome::new(Vec::new(), 2, 1)).collect();
+        for (genome, &fitness) in genomes.iter_mut().zip([1.0, 5.0, 3.0, 0.0].iter()) {
+            genome.fitness = fitness;
+        }
+
+        // The last genome is too young to judge
+        let (replaced, _) = creator.replace_worst(&genomes.iter().collect::<Vec<_>>(),
+                                                  &[true, true, true, false]).unwrap();
+        assert!(replaced == 0);
+        assert!(creator.replace_worst(&genomes.iter().collect::<Vec<_>>(), &[false; 4]).is_none());
+
+        let stats = creator.report(genomes.iter().collect());
+        assert!(stats.generation == 1 && stats.max_fitness == 5.0);
+    }
+
+    #[test]
+    fn steady_state_archives_only_retired_genomes() {
+        let mut config = seeded_config();
+        config.objective = Objective::Novelty;
+        config.novelty_threshold = 0.0;
+        let mut creator = Creator::with_config(config);
+        let mut genomes = creator.random_genomes(6, 2, 1);
+        for (i, genome) in genomes.iter_mut().enumerate() {
+            genome.behavior = vec![i as f64, 0.0];
+        }
+
+        for retired in 1..4 {
+            let (replaced, child) = creator.replace_worst(&genomes.iter().collect::<Vec<_>>(),
+                                                          &[true; 6]).unwrap();
+            genomes[replaced] = child;
+            assert!(creator.archive.behaviors.len() == retired);
+
+            // The child took the place of the replaced genome in the species
+            let members = creator.species.iter().fold(0, |acc, spec| acc + spec.genomes.len());
+            assert!(members == 6);
+        }
+
+        // Reporting doesn't retire anyone
+        creator.report(genomes.iter().collect());
+        assert!(creator.archive.behaviors.len() == 3);
+    }
+
+    #[test]
+    fn steady_state_sorts_a_new_population_from_scratch() {
+        let mut creator = Creator::with_config(seeded_config());
+        let genomes = creator.random_genomes(6, 2, 1);
+        creator.replace_worst(&genomes.iter().collect::<Vec<_>>(), &[true; 6]).unwrap();
+
+        // Same size, different genomes, nothing of the old population stays in the species
+        let mut fresh = creator.random_genomes(6, 2, 1);
+        for genome in fresh.iter_mut() {
+            genome.mutate_node(&mut creator.rng, &mut creator.innovations);
+        }
+        let (replaced, child) = creator.replace_worst(&fresh.iter().collect::<Vec<_>>(),
+                                                      &[true; 6]).unwrap();
+        fresh[replaced] = child;
+
+        let held: Vec<&Genome> = creator.species.iter().flat_map(|spec| spec.genomes.iter()).collect();
+        assert!(held.len() == 6);
+        assert!(fresh.iter().all(|genome| held.iter().any(|other| other.genes == genome.genes)));
+        assert!(creator.holds(&fresh.iter().collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn judged_genomes_enter_hall_of_fame_with_judged_score() {
+        let mut creator = Creator::with_config(seeded_config());
+        creator.set_judge(Box::new(|genome: &Genome| genome.genes.len() as f64 * 100.0));
+        let mut genomes = creator.random_genomes(10, 2, 1);
+        for (i, genome) in genomes.iter_mut().enumerate() {
+            genome.fitness = if i == 5 { f64::NAN } else { i as f64 };
+        }
+
+        creator.next_generation(genomes.iter().collect());
+        assert!(creator.hall_of_fame.len() == 3);
+        for entry in creator.hall_of_fame.entries() {
+            assert!(entry.fitness == entry.genome.genes.len() as f64 * 100.0);
+            assert!(entry.genome.fitness >= 7.0);
+        }
+    }
+
+    #[test]
+    fn offspring_allocation_sums_to_total() {
+        let allocation = super::allocate_offspring(&[1.0, 1.0, 1.0], 10);
+        assert!(allocation.iter().fold(0, |acc, &n| acc + n) == 10);
+
+        let allocation = super::allocate_offspring(&[0.0, 0.0], 5);
+        assert!(allocation.iter().fold(0, |acc, &n| acc + n) == 5);
+
+        let allocation = super::allocate_offspring(&[3.0, 1.0], 4);
+        assert!(allocation == vec![3, 1]);
+
+        for shares in [[f64::INFINITY, 1.0], [f64::NAN, 1.0]].iter() {
+            let allocation = super::allocate_offspring(shares, 4);
+            assert!(allocation.iter().fold(0, |acc, &n| acc + n) == 4);
+        }
     }
 }
@@ -100,6 +100,10 @@ impl Network {
         };
     }
 
+    pub fn num_hidden(&self) -> usize {
+        self.neurons.len() - (self.num_inputs + self.num_outputs) as usize
+    }
+
     pub fn evaluate(&self, inputs: Vec<f64>) -> Vec<f64> {
         // Initialize a network of neuron calculations
         let size = self.neurons.len();
@@ -0,0 +1,112 @@
+use neat;
+use neat::genetics::Genome;
+
+// Remembers behaviors that were novel when they were seen, so the population is pushed away from
+// places it has already been and not just away from itself.
+pub struct NoveltyArchive {
+    pub behaviors: Vec<Vec<f64>>,
+    // Number of nearest neighbours averaged for sparseness
+    neighbours: usize,
+    // Sparseness needed to get into the archive
+    threshold: f64
+}
+
+impl NoveltyArchive {
+    pub fn new(neighbours: usize, threshold: f64) -> NoveltyArchive {
+        NoveltyArchive {
+            behaviors: Vec::new(),
+            neighbours: neighbours,
+            threshold: threshold
+        }
+    }
+
+    // Average distance to the k nearest behaviors among the rest of the population and the archive.
+    // Behaviors are whatever the task reports, distances that come out NaN are left out.
+    pub fn sparseness(&self, idx: usize, population: &[&Vec<f64>]) -> f64 {
+        let behavior = population[idx];
+        let mut distances: Vec<f64> = population.iter().enumerate()
+            .filter(|&(i, _)| i != idx)
+            .map(|(_, other)| distance(behavior, other))
+            .chain(self.behaviors.iter().map(|other| distance(behavior, other)))
+            .filter(|distance| !distance.is_nan())
+            .collect();
+
+        if distances.len() == 0 {
+            return 0f64;
+        }
+
+        distances.sort_by(|&a, &b| neat::compare(a, b));
+        let k = self.neighbours.min(distances.len());
+        distances.iter().take(k).fold(0f64, |acc, d| acc + d) / k as f64
+    }
+
+    // Novelty of every genome, novel enough behaviors are added to the archive afterwards
+    pub fn score(&mut self, genomes: &[&Genome]) -> Vec<f64> {
+        let novelty = self.novelty(genomes);
+        for (genome, &score) in genomes.iter().zip(novelty.iter()) {
+            self.retire(genome, score);
+        }
+        novelty
+    }
+
+    // Novelty of every genome without touching the archive
+    pub fn novelty(&self, genomes: &[&Genome]) -> Vec<f64> {
+        let behaviors: Vec<&Vec<f64>> = genomes.iter().map(|genome| &genome.behavior).collect();
+        (0..behaviors.len()).map(|i| self.sparseness(i, &behaviors)).collect()
+    }
+
+    // Remember the behavior of a genome leaving the population if it was novel enough
+    pub fn retire(&mut self, genome: &Genome, novelty: f64) {
+        if novelty > self.threshold {
+            self.behaviors.push(genome.behavior.clone());
+        }
+    }
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).fold(0f64, |acc, (x, y)| acc + (x - y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat::genetics::{Gene, Genome};
+
+    #[test]
+    fn outliers_are_novel() {
+        let genome = Genome::new(vec![
+            Gene{ into: 0, out: 2, weight: 1.0, enabled: true, innovation: 1 }
+        ], 2, 1);
+        let mut genomes: Vec<Genome> = (0..3).map(|_| genome.clone()).collect();
+        genomes[0].behavior = vec![0.0, 0.0];
+        genomes[1].behavior = vec![0.0, 1.0];
+        genomes[2].behavior = vec![10.0, 0.0];
+
+        let mut archive = NoveltyArchive::new(2, 8.0);
+        let novelty = archive.score(&genomes.iter().collect::<Vec<_>>());
+
+        assert!(novelty[2] > novelty[0] && novelty[2] > novelty[1]);
+        assert!(archive.behaviors == vec![vec![10.0, 0.0]]);
+
+        // Looking doesn't add anything
+        assert!(archive.novelty(&genomes.iter().collect::<Vec<_>>()).len() == 3);
+        assert!(archive.behaviors.len() == 1);
+    }
+
+    #[test]
+    fn nan_behaviors_are_not_novel() {
+        let genome = Genome::new(vec![
+            Gene{ into: 0, out: 2, weight: 1.0, enabled: true, innovation: 1 }
+        ], 2, 1);
+        let mut genomes: Vec<Genome> = (0..3).map(|_| genome.clone()).collect();
+        genomes[0].behavior = vec![0.0, 0.0];
+        genomes[1].behavior = vec![f64::NAN, 1.0];
+        genomes[2].behavior = vec![3.0, 4.0];
+
+        let mut archive = NoveltyArchive::new(2, 8.0);
+        let novelty = archive.score(&genomes.iter().collect::<Vec<_>>());
+
+        assert!(novelty == vec![5.0, 0.0, 5.0]);
+        assert!(archive.behaviors.len() == 0);
+    }
+}
@@ -0,0 +1,133 @@
+// NSGA-II style ranking for genomes with several objectives, every objective is maximized
+
+use neat;
+
+use std::f64;
+
+// Front each point belongs to, 0 is the non-dominated front
+pub fn non_dominated_sort(points: &[&Vec<f64>]) -> Vec<usize> {
+    let n = points.len();
+    let mut dominates: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut dominated_by = vec![0usize; n];
+
+    for i in 0..n {
+        for j in 0..n {
+            if dominated(points[j], points[i]) {
+                dominates[i].push(j);
+            } else if dominated(points[i], points[j]) {
+                dominated_by[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = vec![0usize; n];
+    let mut current: Vec<usize> = (0..n).filter(|&i| dominated_by[i] == 0).collect();
+    let mut front = 0;
+    while current.len() > 0 {
+        let mut next = Vec::new();
+        for &i in current.iter() {
+            fronts[i] = front;
+            for &j in dominates[i].iter() {
+                dominated_by[j] -= 1;
+                if dominated_by[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        front += 1;
+        current = next;
+    }
+
+    fronts
+}
+
+// How isolated each member of a front is along every objective, the extremes are infinite. NaN
+// objective values sort below everything and leave that objective out of the spread.
+pub fn crowding_distance(points: &[&Vec<f64>], members: &[usize]) -> Vec<f64> {
+    let mut distance = vec![0f64; members.len()];
+    if members.len() == 0 {
+        return distance;
+    }
+
+    for objective in 0..points[members[0]].len() {
+        let mut order: Vec<usize> = (0..members.len()).collect();
+        order.sort_by(|&a, &b|
+            neat::compare(points[members[a]][objective], points[members[b]][objective]));
+
+        let min = points[members[order[0]]][objective];
+        let max = points[members[order[order.len() - 1]]][objective];
+        distance[order[0]] = f64::INFINITY;
+        distance[order[order.len() - 1]] = f64::INFINITY;
+        if !(max > min) {
+            continue;
+        }
+
+        for k in 1..(order.len() - 1) {
+            let prev = points[members[order[k - 1]]][objective];
+            let next = points[members[order[k + 1]]][objective];
+            distance[order[k]] += (next - prev) / (max - min);
+        }
+    }
+
+    distance
+}
+
+// Single score that orders genomes by front first and crowding distance second. A whole front
+// lies within half a point of minus its index, so a worse front never outranks a better one.
+pub fn pareto_fitness(points: &[&Vec<f64>]) -> Vec<f64> {
+    let fronts = non_dominated_sort(points);
+    let mut fitness = vec![0f64; points.len()];
+
+    let num_fronts = fronts.iter().fold(0, |acc, &f| acc.max(f + 1));
+    for front in 0..num_fronts {
+        let members: Vec<usize> = (0..points.len()).filter(|&i| fronts[i] == front).collect();
+        let crowding = crowding_distance(points, &members);
+        for (&i, &c) in members.iter().zip(crowding.iter()) {
+            let spread = if c.is_infinite() { 1f64 } else { c / (1f64 + c) };
+            fitness[i] = -(front as f64) + 0.5 * spread;
+        }
+    }
+
+    fitness
+}
+
+// Whether `a` is dominated by `b`: no better in any objective and worse in at least one
+fn dominated(a: &[f64], b: &[f64]) -> bool {
+    let mut worse = false;
+    for (x, y) in a.iter().zip(b.iter()) {
+        if x > y {
+            return false;
+        }
+        if x < y {
+            worse = true;
+        }
+    }
+    worse
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_are_sorted_into_fronts() {
+        let points = vec![vec![1.0, 4.0], vec![4.0, 1.0], vec![2.0, 2.0], vec![1.0, 1.0]];
+        let fronts = non_dominated_sort(&points.iter().collect::<Vec<_>>());
+        assert!(fronts == vec![0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn better_fronts_score_higher() {
+        let points = vec![vec![1.0, 4.0], vec![4.0, 1.0], vec![2.0, 2.0], vec![1.0, 1.0]];
+        let fitness = pareto_fitness(&points.iter().collect::<Vec<_>>());
+        assert!(fitness[3] < fitness[0] && fitness[3] < fitness[1] && fitness[3] < fitness[2]);
+        assert!(fitness[2] < fitness[0]);
+    }
+
+    #[test]
+    fn nan_objectives_get_a_distance() {
+        let points = vec![vec![f64::NAN, 1.0], vec![1.0, 2.0], vec![2.0, 1.0], vec![1.5, 1.5]];
+        let distance = crowding_distance(&points.iter().collect::<Vec<_>>(), &[0, 1, 2, 3]);
+        assert!(distance.iter().all(|d| !d.is_nan()));
+    }
+}
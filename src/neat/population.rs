@@ -0,0 +1,121 @@
+use neat::Creator;
+use neat::genetics::Genome;
+use neat::stats::GenerationStats;
+use neat::task::{Evaluation, Task};
+
+use std::mem;
+
+// Genomes evolving on a task, takes care of evaluation, speciation and reproduction
+pub struct Population {
+    pub genomes: Vec<Genome>,
+    pub creator: Creator,
+    // Best genome evaluated so far
+    pub champion: Option<Genome>,
+    // Latest genomes evaluated, and their evaluations. They're moved here when their offspring
+    // replace them.
+    pub parents: Vec<Genome>,
+    pub evaluations: Vec<Evaluation>
+}
+
+impl Population {
+    pub fn new<T: Task>(task: &T, size: usize, creator: Creator) -> Population {
+        let mut creator = creator;
+        let genomes = creator.random_genomes(size, task.num_inputs(), task.num_outputs());
+        Population::from_genomes(genomes, creator)
+    }
+
+    pub fn from_genomes(genomes: Vec<Genome>, creator: Creator) -> Population {
+        Population {
+            genomes: genomes,
+            creator: creator,
+            champion: None,
+            parents: Vec::new(),
+            evaluations: Vec::new()
+        }
+    }
+
+    pub fn evaluate<T: Task>(&mut self, task: &mut T) -> &[Evaluation] {
+        let mut evaluations = Vec::with_capacity(self.genomes.len());
+        for genome in self.genomes.iter_mut() {
+            let evaluation = task.evaluate(&genome.network);
+            genome.fitness = evaluation.fitness;
+            genome.objectives = evaluation.objectives.clone();
+            genome.behavior = evaluation.behavior.clone();
+            evaluations.push(evaluation);
+
+            let improved = match self.champion {
+                Some(ref champion) => genome.fitness > champion.fitness,
+                None => true
+            };
+            if improved {
+                self.champion = Some(genome.clone());
+            }
+        }
+
+        self.evaluations = evaluations;
+        &self.evaluations
+    }
+
+    // Replace the evaluated genomes with their offspring
+    pub fn reproduce(&mut self) -> GenerationStats {
+        let (offspring, stats) = self.creator.next_generation(self.genomes.iter().collect());
+        self.parents = mem::replace(&mut self.genomes, offspring);
+        stats
+    }
+
+    // Evaluate the current genomes and replace them with their offspring
+    pub fn step<T: Task>(&mut self, task: &mut T) -> GenerationStats {
+        self.evaluate(task);
+        self.reproduce()
+    }
+
+    pub fn run<T: Task>(&mut self, task: &mut T, generations: u64) -> Option<&Genome> {
+        for _ in 0..generations {
+            self.step(task);
+        }
+        self.champion.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat::Creator;
+    use neat::config::Config;
+    use neat::neurology::Network;
+    use neat::task::{Evaluation, Task};
+
+    fn seeded_creator() -> Creator {
+        let mut config = Config::new();
+        config.seed = Some(1);
+        Creator::with_config(config)
+    }
+
+    // Output should be as large as possible
+    struct Maximize;
+
+    impl Task for Maximize {
+        fn num_inputs(&self) -> u64 { 2 }
+        fn num_outputs(&self) -> u64 { 1 }
+        fn evaluate(&mut self, network: &Network) -> Evaluation {
+            Evaluation::new(network.evaluate(vec![1f64, 1f64])[0])
+        }
+    }
+
+    #[test]
+    fn population_runs_task() {
+        let mut task = Maximize;
+        let mut population = Population::new(&task, 20, seeded_creator());
+        let champion_fitness = population.run(&mut task, 10).unwrap().fitness;
+
+        assert!(population.genomes.len() == 20);
+        assert!(population.creator.generation() == 10);
+        assert!(champion_fitness >= -1f64 && champion_fitness <= 1f64);
+
+        // The last generation evaluated is kept next to its offspring
+        assert!(population.parents.len() == 20 && population.evaluations.len() == 20);
+        for (genome, evaluation) in population.parents.iter().zip(population.evaluations.iter()) {
+            assert!(genome.fitness == evaluation.fitness);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use neat;
+
+use rand::{Rng, StdRng};
+use rand::distributions::{IndependentSample, Range};
+
+// Strategy for picking a parent out of a group of candidates. Candidates are given by their
+// fitness only, the returned value is the index of the chosen one.
+pub trait Selection {
+    fn select(&self, fitness: &[f64], rng: &mut StdRng) -> usize;
+}
+
+// Best of `size` candidates picked uniformly at random
+pub struct Tournament {
+    pub size: usize
+}
+
+impl Selection for Tournament {
+    fn select(&self, fitness: &[f64], rng: &mut StdRng) -> usize {
+        let candidates = Range::new(0usize, fitness.len());
+        let mut best = candidates.ind_sample(rng);
+        for _ in 1..self.size {
+            let candidate = candidates.ind_sample(rng);
+            if fitness[candidate] > fitness[best] {
+                best = candidate;
+            }
+        }
+        best
+    }
+}
+
+// Fitness proportional (roulette wheel). Fitness is shifted so the worst candidate has no chance
+// unless every candidate is equally fit.
+pub struct Roulette;
+
+impl Selection for Roulette {
+    fn select(&self, fitness: &[f64], rng: &mut StdRng) -> usize {
+        let min = fitness.iter().fold(fitness[0], |acc, &f| acc.min(f));
+        let weights: Vec<f64> = fitness.iter().map(|&f| f - min).collect();
+        spin(&weights, rng)
+    }
+}
+
+// Chance is proportional to rank instead of raw fitness, the best of n candidates gets weight n
+// and the worst gets weight 1. NaN fitness ranks below everything.
+pub struct Rank;
+
+impl Selection for Rank {
+    fn select(&self, fitness: &[f64], rng: &mut StdRng) -> usize {
+        let mut order: Vec<usize> = (0..fitness.len()).collect();
+        order.sort_by(|&a, &b| neat::compare(fitness[a], fitness[b]));
+
+        let mut weights = vec![0f64; fitness.len()];
+        for (rank, &i) in order.iter().enumerate() {
+            weights[i] = (rank + 1) as f64;
+        }
+        spin(&weights, rng)
+    }
+}
+
+// Every candidate is equally likely, fitness is ignored
+pub struct Uniform;
+
+impl Selection for Uniform {
+    fn select(&self, fitness: &[f64], rng: &mut StdRng) -> usize {
+        rng.gen_range(0usize, fitness.len())
+    }
+}
+
+// Index picked with probability proportional to its weight, uniformly when they're all zero
+pub fn spin(weights: &[f64], rng: &mut StdRng) -> usize {
+    let total = weights.iter().fold(0f64, |acc, w| acc + w);
+    if total <= 0f64 {
+        return rng.gen_range(0usize, weights.len());
+    }
+
+    let mut target = Range::new(0f64, total).ind_sample(rng);
+    for (i, &weight) in weights.iter().enumerate() {
+        if target < weight {
+            return i;
+        }
+        target -= weight;
+    }
+    weights.len() - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat;
+
+    #[test]
+    fn selections_pick_valid_candidates() {
+        let fitness = vec![-3.0, 1.0, -1.0, 2.0];
+        let selections: Vec<Box<Selection>> = vec![
+            Box::new(Tournament { size: 4 }), Box::new(Roulette), Box::new(Rank), Box::new(Uniform)
+        ];
+        let mut rng = neat::seeded_rng(1);
+        for selection in selections.iter() {
+            assert!(selection.select(&fitness, &mut rng) < fitness.len());
+        }
+    }
+
+    #[test]
+    fn rank_puts_nan_fitness_last() {
+        let fitness = vec![f64::NAN, 1.0, f64::NAN, 2.0];
+        let mut rng = neat::seeded_rng(1);
+        let picks: Vec<usize> = (0..20).map(|_| Rank.select(&fitness, &mut rng)).collect();
+        assert!(picks.iter().any(|&i| i == 3));
+    }
+
+    #[test]
+    fn roulette_never_picks_worst_of_unequal() {
+        let fitness = vec![-3.0, -1.0];
+        let mut rng = neat::seeded_rng(1);
+        for _ in 0..10 {
+            assert!(Roulette.select(&fitness, &mut rng) == 1);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use neat::genetics::Genome;
+use neat::taxonomy::Species;
+
+use std::f64;
+
+#[derive(Clone, Debug)]
+pub struct SpeciesStats {
+    pub id: u64,
+    pub size: usize,
+    pub average_fitness: f64,
+    pub best_fitness: f64,
+    pub staleness: u64
+}
+
+impl SpeciesStats {
+    // `objective` holds the objective fitness of each member of the species. Fitness is of the
+    // current members, the species' best ever only shows through its staleness.
+    pub fn new(species: &Species, objective: &[f64]) -> SpeciesStats {
+        SpeciesStats {
+            id: species.id,
+            size: species.genomes.len(),
+            average_fitness: objective.iter().fold(0f64, |acc, f| acc + f) / objective.len() as f64,
+            best_fitness: objective.iter().fold(f64::NEG_INFINITY, |acc, &f| acc.max(f)),
+            staleness: species.staleness
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub min_fitness: f64,
+    pub mean_fitness: f64,
+    pub max_fitness: f64,
+    pub stddev_fitness: f64,
+    pub species: Vec<SpeciesStats>,
+    pub mean_genes: f64,
+    pub mean_hidden_nodes: f64,
+    pub compatibility_threshold: f64,
+    pub innovation: u64
+}
+
+impl GenerationStats {
+    // `fitness` is what each genome is reported by, see `Creator::headline_fitness`
+    pub fn new(generation: u64, genomes: &[&Genome], fitness: &[f64]) -> GenerationStats {
+        let size = genomes.len() as f64;
+
+        let min = fitness.iter().fold(fitness[0], |acc, &f| acc.min(f));
+        let max = fitness.iter().fold(fitness[0], |acc, &f| acc.max(f));
+        let mean = fitness.iter().fold(0f64, |acc, f| acc + f) / size;
+        let variance = fitness.iter().fold(0f64, |acc, f| acc + (f - mean).powi(2)) / size;
+
+        let genes = genomes.iter().fold(0, |acc, genome| acc + genome.genes.len());
+        let hidden = genomes.iter().fold(0, |acc, genome| acc + genome.network.num_hidden());
+
+        GenerationStats {
+            generation: generation,
+            min_fitness: min,
+            mean_fitness: mean,
+            max_fitness: max,
+            stddev_fitness: variance.sqrt(),
+            species: Vec::new(),
+            mean_genes: genes as f64 / size,
+            mean_hidden_nodes: hidden as f64 / size,
+            compatibility_threshold: 0f64,
+            innovation: 0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neat::genetics::{Gene, Genome};
+    use neat::taxonomy::Species;
+
+    #[test]
+    fn stats_summarize_fitness() {
+        let genome1 = Genome::new(vec![
+            Gene{ into: 0, out: 4, weight: 1.0, enabled: true, innovation: 1 },
+            Gene{ into: 4, out: 2, weight: 1.0, enabled: true, innovation: 2 }
+        ], 2, 1);
+        let genome2 = Genome::new(vec![
+            Gene{ into: 0, out: 2, weight: 1.0, enabled: true, innovation: 1 }
+        ], 2, 1);
+        let stats = GenerationStats::new(1, &[&genome1, &genome2], &[-1.0, -3.0]);
+
+        assert!(stats.min_fitness == -3.0);
+        assert!(stats.max_fitness == -1.0);
+        assert!(stats.mean_fitness == -2.0);
+        assert!(stats.stddev_fitness == 1.0);
+        assert!(stats.mean_genes == 1.5);
+        assert!(stats.mean_hidden_nodes == 0.5);
+    }
+
+    #[test]
+    fn species_stats_describe_current_members() {
+        let genome = Genome::new(vec![
+            Gene{ into: 0, out: 2, weight: 1.0, enabled: true, innovation: 1 }
+        ], 2, 1);
+        let mut species = Species::new(1, genome);
+        species.calc_staleness(5.0);
+        assert!(SpeciesStats::new(&species, &[5.0]).best_fitness == 5.0);
+
+        // The members got worse, the species' record stays behind
+        species.calc_staleness(2.0);
+        let stats = SpeciesStats::new(&species, &[2.0]);
+        assert!(stats.best_fitness == 2.0 && stats.average_fitness == 2.0);
+        assert!(stats.staleness == 1 && species.best_fitness == 5.0);
+    }
+}
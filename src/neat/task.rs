@@ -0,0 +1,32 @@
+use neat::neurology::Network;
+
+use std::collections::HashMap;
+
+// Result of running a network on a task
+pub struct Evaluation {
+    pub fitness: f64,
+    // Scores for multi-objective runs, see `Genome::objectives`
+    pub objectives: Vec<f64>,
+    // Behavior descriptor for novelty search, see `Genome::behavior`
+    pub behavior: Vec<f64>,
+    // Anything else the task wants to report
+    pub metadata: HashMap<String, f64>
+}
+
+impl Evaluation {
+    pub fn new(fitness: f64) -> Evaluation {
+        Evaluation {
+            fitness: fitness,
+            objectives: Vec::new(),
+            behavior: Vec::new(),
+            metadata: HashMap::new()
+        }
+    }
+}
+
+// Anything a network can be evolved to do
+pub trait Task {
+    fn num_inputs(&self) -> u64;
+    fn num_outputs(&self) -> u64;
+    fn evaluate(&mut self, network: &Network) -> Evaluation;
+}
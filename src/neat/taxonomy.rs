@@ -1,21 +1,41 @@
 use neat;
-use neat::genetics::Genome;
+use neat::genetics::{Genome, Innovations};
+use neat::selection::Selection;
 
-use rand::Rng;
+use rand::{Rng, StdRng};
+
+use std::f64;
 
 const EXCESS_COEFF: f64 = 1.0;
 const DISJOINT_COEFF: f64 = 1.0;
 const WEIGHT_COEFF: f64 = 1.0;
-const DIFFERENCE_THRESHOLD: f64 = 1.0;
+pub const DIFFERENCE_THRESHOLD: f64 = 1.0;
 const CULL_PERCENTAGE: f64 = 0.5;
 
 pub struct Species {
+    pub id: u64,
     pub representative: Genome,
     pub genomes: Vec<Genome>,
-    pub avg_fitness: f64
+    pub avg_fitness: f64,
+    pub adjusted_fitness: f64,
+    // Best fitness the species has ever reached and generations since it last improved on it
+    pub best_fitness: f64,
+    pub staleness: u64
 }
 
 impl Species {
+    pub fn new(id: u64, representative: Genome) -> Species {
+        Species {
+            id: id,
+            genomes: vec![representative.clone()],
+            representative: representative,
+            avg_fitness: 0f64,
+            adjusted_fitness: 0f64,
+            best_fitness: f64::NEG_INFINITY,
+            staleness: 0
+        }
+    }
+
     pub fn difference(genome1: &Genome, genome2: &Genome) -> f64 {
         let mut disjoint = Vec::new();
         let mut excess = Vec::new();
@@ -71,24 +91,59 @@ impl Species {
     }
 
     pub fn cull(&mut self) {
-        self.genomes.sort_by( |genome1, genome2| genome1.fitness.partial_cmp(&genome2.fitness).unwrap().reverse() );
-        let split_idx = self.genomes.len() as f64 * CULL_PERCENTAGE;
-        self.genomes.split_off(split_idx as usize);
+        self.genomes.sort_by( |genome1, genome2| neat::compare(genome1.fitness, genome2.fitness).reverse() );
+        // Always keep the champion so a species isn't wiped out by rounding
+        let split_idx = (self.genomes.len() as f64 * CULL_PERCENTAGE).max(1f64);
+        self.genomes.truncate(split_idx as usize);
     }
 
     pub fn add_genome(&mut self, genome: Genome) {
         self.genomes.push(genome);
     }
 
-    pub fn breed_child(&self) -> Genome {
-        let mut rng = neat::rng();
-        let child1 = rng.choose(&self.genomes).unwrap();
-        let child2 = rng.choose(&self.genomes).unwrap();
-        return child1.breed(&child2);
+    pub fn fitnesses(&self) -> Vec<f64> {
+        self.genomes.iter().map(|genome| genome.fitness).collect()
+    }
+
+    pub fn select_parent(&self, selection: &Selection, rng: &mut StdRng) -> &Genome {
+        &self.genomes[selection.select(&self.fitnesses(), rng)]
+    }
+
+    pub fn breed_child(&self, selection: &Selection, rng: &mut StdRng,
+                       innovations: &mut Innovations) -> Genome {
+        let mut fitnesses = self.fitnesses();
+        let parent1 = selection.select(&fitnesses, rng);
+        if self.genomes.len() == 1 {
+            return self.genomes[parent1].breed(&self.genomes[parent1], rng, innovations);
+        }
+
+        // Pick the second parent from everyone else so a genome doesn't mate with itself
+        fitnesses.remove(parent1);
+        let mut parent2 = selection.select(&fitnesses, rng);
+        if parent2 >= parent1 {
+            parent2 += 1;
+        }
+        return self.genomes[parent1].breed(&self.genomes[parent2], rng, innovations);
+    }
+
+    pub fn breed_child_with(&self, mate: &Genome, selection: &Selection, rng: &mut StdRng,
+                            innovations: &mut Innovations) -> Genome {
+        return self.select_parent(selection, rng).breed(mate, rng, innovations);
     }
 
-    pub fn assign_representative(&mut self) {
-        self.representative = (*neat::rng().choose(&self.genomes).unwrap()).clone();
+    pub fn assign_representative(&mut self, rng: &mut StdRng) {
+        self.representative = (*rng.choose(&self.genomes).unwrap()).clone();
+    }
+
+    // `best` is the best objective fitness among the members, which can differ from the fitness
+    // they are selected on
+    pub fn calc_staleness(&mut self, best: f64) {
+        if best > self.best_fitness {
+            self.best_fitness = best;
+            self.staleness = 0;
+        } else {
+            self.staleness += 1;
+        }
     }
 
     pub fn calc_average_fitness(&mut self) {
@@ -99,6 +154,18 @@ impl Species {
     pub fn average_fitness(&self) -> f64 {
         return self.avg_fitness;
     }
+
+    // Explicit fitness sharing: each genome's fitness is shifted by `offset` so it is non-negative,
+    // then divided by the size of the species so large species can't take over the population.
+    pub fn calc_adjusted_fitness(&mut self, offset: f64) {
+        let size = self.genomes.len() as f64;
+        self.adjusted_fitness = self.genomes.iter().fold(0f64,
+            |acc, genome| acc + (genome.fitness + offset) / size);
+    }
+
+    pub fn adjusted_fitness(&self) -> f64 {
+        return self.adjusted_fitness;
+    }
 }
 
 #[cfg(test)]
@@ -116,11 +183,7 @@ mod tests {
 
         let genome2 = genome1.clone();
 
-        let species = Species {
-            representative: genome1,
-            genomes: Vec::new(),
-            avg_fitness: 0f64
-        };
+        let species = Species::new(1, genome1);
 
         assert!(species.compatible(&genome2) == true);
     }
@@ -139,12 +202,24 @@ mod tests {
             Gene{ into: 2, out: 3, weight: 5.0, enabled: true, innovation: 6 }
         ], 2, 1);
 
-        let species = Species {
-            representative: genome1,
-            genomes: Vec::new(),
-            avg_fitness: 0f64
-        };
+        let species = Species::new(1, genome1);
 
         assert!(species.compatible(&genome2) == false);
     }
+
+    #[test]
+    fn species_share_adjusted_fitness() {
+        let mut genome1 = Genome::new(vec![
+            Gene{ into: 0, out: 3, weight: 1.0, enabled: true, innovation: 1 }
+        ], 2, 1);
+        let mut genome2 = genome1.clone();
+        genome1.fitness = -4.0;
+        genome2.fitness = -2.0;
+
+        let mut species = Species::new(1, genome1);
+        species.add_genome(genome2);
+        species.calc_adjusted_fitness(4.0);
+
+        assert!(species.adjusted_fitness() == 1.0);
+    }
 }
@@ -0,0 +1,113 @@
+// How forces on an actor turn into motion
+
+use na::{Pnt2, Vec2};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Integrator {
+    // Velocity first, then position with the new velocity. Cheap and stable.
+    SemiImplicitEuler,
+    // Classic fourth order Runge-Kutta, more accurate with strong drag
+    RungeKutta4
+}
+
+#[derive(Clone, Debug)]
+pub struct Physics {
+    pub mass: f64,
+    // Force against the motion per unit of velocity
+    pub drag: f64,
+    pub max_speed: f64,
+    // Most force an actor can put out itself, external forces aren't limited
+    pub max_force: f64,
+    pub integrator: Integrator,
+    // Forces pushed on every actor every tick, like wind. Gravity has to be multiplied by the mass.
+    pub fields: Vec<Vec2<f64>>
+}
+
+impl Physics {
+    pub fn new() -> Physics {
+        Physics {
+            mass: 1f64,
+            drag: 1f64,
+            max_speed: 200f64,
+            max_force: 100f64,
+            integrator: Integrator::SemiImplicitEuler,
+            fields: Vec::new()
+        }
+    }
+
+    // Acceleration under `force` while moving at `velocity`
+    fn acceleration(&self, force: Vec2<f64>, velocity: Vec2<f64>) -> Vec2<f64> {
+        (force - velocity * self.drag) / self.mass
+    }
+
+    // Position and velocity after `dt` seconds under a constant force
+    pub fn integrate(&self, position: Pnt2<f64>, velocity: Vec2<f64>, force: Vec2<f64>,
+                     dt: f64) -> (Pnt2<f64>, Vec2<f64>) {
+        let (position, velocity) = match self.integrator {
+            Integrator::SemiImplicitEuler => {
+                let velocity = velocity + self.acceleration(force, velocity) * dt;
+                (position + velocity * dt, velocity)
+            },
+            Integrator::RungeKutta4 => {
+                let half = dt / 2f64;
+                let (k1x, k1v) = (velocity, self.acceleration(force, velocity));
+                let (k2x, k2v) = (velocity + k1v * half, self.acceleration(force, velocity + k1v * half));
+                let (k3x, k3v) = (velocity + k2v * half, self.acceleration(force, velocity + k2v * half));
+                let (k4x, k4v) = (velocity + k3v * dt, self.acceleration(force, velocity + k3v * dt));
+                (position + (k1x + k2x * 2f64 + k3x * 2f64 + k4x) * (dt / 6f64),
+                 velocity + (k1v + k2v * 2f64 + k3v * 2f64 + k4v) * (dt / 6f64))
+            }
+        };
+        (position, clamp(velocity, self.max_speed))
+    }
+}
+
+// Same direction, no longer than `max`
+pub fn clamp(v: Vec2<f64>, max: f64) -> Vec2<f64> {
+    let length = (v.x * v.x + v.y * v.y).sqrt();
+    if length > max { v * (max / length) } else { v }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use na::{Pnt2, Vec2};
+
+    #[test]
+    fn drag_and_clamps_limit_speed() {
+        let mut physics = Physics::new();
+        physics.drag = 2f64;
+        physics.max_speed = 1000f64;
+
+        // Terminal velocity is force / drag
+        let force = Vec2::new(100f64, 0f64);
+        let (mut position, mut velocity) = (Pnt2::new(0f64, 0f64), Vec2::new(0f64, 0f64));
+        for _ in 0..1000 {
+            let (p, v) = physics.integrate(position, velocity, force, 0.1f64);
+            position = p;
+            velocity = v;
+        }
+        assert!((velocity.x - 50f64).abs() < 1e-6);
+
+        physics.max_speed = 10f64;
+        let (_, velocity) = physics.integrate(position, velocity, force, 0.1f64);
+        assert!(velocity == Vec2::new(10f64, 0f64));
+    }
+
+    #[test]
+    fn runge_kutta_follows_exact_solution_closer() {
+        let mut physics = Physics::new();
+        physics.drag = 5f64;
+        let start = (Pnt2::new(0f64, 0f64), Vec2::new(100f64, 0f64));
+        let dt = 0.1f64;
+
+        // Coasting under drag alone slows to v0 * e^(-drag * t)
+        let exact = 100f64 * (-5f64 * dt).exp();
+        let (_, euler) = physics.integrate(start.0, start.1, Vec2::new(0f64, 0f64), dt);
+        physics.integrator = Integrator::RungeKutta4;
+        let (_, rk4) = physics.integrate(start.0, start.1, Vec2::new(0f64, 0f64), dt);
+        assert!((rk4.x - exact).abs() < (euler.x - exact).abs());
+        assert!((rk4.x - exact).abs() < 0.1f64);
+    }
+}
@@ -1,6 +1,19 @@
+pub mod boundary;
+pub mod coevolution;
+pub mod config;
+pub mod energy;
+pub mod entities;
+pub mod fitness;
 pub mod neat;
+pub mod physics;
+pub mod sensors;
 
+use config::{Control, Replacement, Spawn, WorldConfig};
+use entities::{Entity, Event};
+use fitness::{Fitness, ReachTarget, RunningTotal, State, Total};
+use neat::control::Evolve;
 use neat::genetics::Genome;
+use neat::stats::GenerationStats;
 use neat::Creator;
 
 #[macro_use]
@@ -8,110 +21,756 @@ extern crate log;
 extern crate nalgebra as na;
 extern crate rand;
 extern crate time;
-use na::{Vec2, Pnt2, FloatPnt};
+use na::{Vec2, Pnt2};
 
-// Updates per second
+use std::f64::consts::PI;
+
+// Updates per second of simulated time, the simulation always moves in steps this size
 pub const TICKS: u64 = 25u64;
 // Seconds Per Tick
 pub const SPT: f64 = 1f64 / TICKS as f64;
-// Seconds per generation
+// Default seconds per generation
 pub const SPG: f64 = 10f64;
-// Ticks per generation
-pub const TPG: u64 = (SPG * TICKS as f64) as u64;
+// Network inputs an actor has without sensors, and its outputs
+pub const ACTOR_INPUTS: u64 = 7;
+pub const ACTOR_OUTPUTS: u64 = 2;
+// Actors are circles this big when colliding with things
+pub const ACTOR_RADIUS: f64 = 5f64;
 
 pub struct Actor {
     pub position: Pnt2<f64>,
     pub velocity: Vec2<f64>,
     pub acceleration: Vec2<f64>,
+    // Radians clockwise from the x axis, kept in 0..2π
+    pub heading: f64,
+    pub angular_velocity: f64,
+    // Force the actor put out itself in the last tick
+    pub thrust: Vec2<f64>,
+    // Only used up when the world has energy turned on
+    pub energy: f64,
+    // Dead actors stop moving and sensing until the next generation
+    pub alive: bool,
+    // State after every tick of the actor's life so far, or at least of the world's evaluation
+    // window
+    pub history: Vec<State>,
+    // Ticks lived
+    pub age: u64,
+    // Index of the world's team the actor plays for
+    pub team: usize,
+    // Veterans play along without evolving
+    pub veteran: bool,
+    // Sum of the forces pushed on the actor this tick
+    force: Vec2<f64>,
     genome: Genome
 }
 
 impl Actor {
-    pub fn new(genome: Genome) -> Actor {
-        let p: Pnt2<f64> = Pnt2::new(320f64, 240f64);
+    pub fn new(genome: Genome, position: Pnt2<f64>) -> Actor {
         let v: Vec2<f64> = Vec2::new(0f64, 0f64);
         let a: Vec2<f64> = Vec2::new(0f64, 0f64);
         Actor {
-            position: p,
+            position: position,
             velocity: v,
             acceleration: a,
+            heading: 0f64,
+            angular_velocity: 0f64,
+            thrust: Vec2::new(0f64, 0f64),
+            energy: 0f64,
+            alive: true,
+            history: Vec::new(),
+            age: 0,
+            team: 0,
+            veteran: false,
+            force: Vec2::new(0f64, 0f64),
             genome: genome
         }
     }
 
+    // Add a force for the next move
     pub fn push(&mut self, force: Vec2<f64>) {
-        self.acceleration = self.acceleration + force;
+        self.force = self.force + force;
     }
 
-    pub fn update(&mut self) {
+    // The actor's own state as network inputs, ending with the bias. Position is scaled to
+    // -0.5..0.5 over the world.
+    pub fn sense(&self, config: &WorldConfig) -> Vec<f64> {
         let mut inputs = Vec::new();
-        inputs.push(self.position.x / 320f64 - 0.5f64);
-        inputs.push(self.position.y / 240f64 - 0.5f64);
+        inputs.push(self.position.x / config.width - 0.5f64);
+        inputs.push(self.position.y / config.height - 0.5f64);
         inputs.push(self.velocity.x);
         inputs.push(self.velocity.y);
         inputs.push(self.acceleration.x);
         inputs.push(self.acceleration.y);
         inputs.push(1f64);
+        inputs
+    }
 
+    pub fn facing(&self) -> Vec2<f64> {
+        Vec2::new(self.heading.cos(), self.heading.sin())
+    }
+
+    // Feed the inputs to the network, push the actor the way the control model reads its outputs
+    // and move it under everything that pushed it this tick
+    pub fn control(&mut self, inputs: Vec<f64>, config: &WorldConfig) {
         let outputs = self.genome.network.evaluate(inputs);
-        let jerk = Vec2::new(outputs[0], outputs[1]);
+        let physics = &config.physics;
+
+        let thrust = match config.control {
+            Control::Force => Vec2::new(outputs[0], outputs[1]) * physics.max_force,
+            Control::DifferentialDrive { wheel_base, max_speed } => {
+                let left = outputs[0] * max_speed;
+                let right = outputs[1] * max_speed;
+                // With y pointing down the left wheel is on the -y side when facing along x
+                self.angular_velocity = (left - right) / wheel_base;
+                self.heading = sensors::wrap_angle(self.heading + self.angular_velocity * SPT);
+
+                // The wheels push as hard as it takes to roll at their speed
+                let velocity = self.facing() * ((left + right) / 2f64);
+                (velocity - self.velocity) * (physics.mass / SPT) + self.velocity * physics.drag
+            },
+            Control::ThrustTurn { max_thrust, max_turn } => {
+                self.angular_velocity = outputs[1] * max_turn;
+                self.heading = sensors::wrap_angle(self.heading + self.angular_velocity * SPT);
+                self.facing() * (outputs[0].max(0f64) * max_thrust)
+            }
+        };
+        self.thrust = physics::clamp(thrust, physics.max_force);
+        let thrust = self.thrust;
+        self.push(thrust);
+
+        let (position, velocity) = physics.integrate(self.position, self.velocity, self.force, SPT);
+        self.acceleration = (velocity - self.velocity) / SPT;
+        self.position = position;
+        self.velocity = velocity;
+        // Forces only last the tick they're pushed in
+        self.force = Vec2::new(0f64, 0f64);
+
+        if config.control == Control::Force {
+            if self.velocity.x != 0f64 || self.velocity.y != 0f64 {
+                let heading = sensors::wrap_angle(self.velocity.y.atan2(self.velocity.x));
+                // Turn the short way round
+                let turn = sensors::wrap_angle(heading - self.heading + PI) - PI;
+                self.angular_velocity = turn / SPT;
+                self.heading = heading;
+            } else {
+                self.angular_velocity = 0f64;
+            }
+        }
+
+        self.genome.behavior = vec![self.position.x, self.position.y];
+    }
+
+    // Add the current state to the history
+    pub fn record(&mut self, events: Vec<Event>, gathered: f64) {
+        let state = State {
+            tick: self.age,
+            position: self.position,
+            velocity: self.velocity,
+            acceleration: self.acceleration,
+            heading: self.heading,
+            energy: self.energy,
+            gathered: gathered,
+            events: events
+        };
+        self.history.push(state);
+        self.age += 1;
+    }
+
+    // The latest states, all of them without a window
+    pub fn recent(&self, window: Option<usize>) -> &[State] {
+        let start = match window {
+            Some(window) => self.history.len().saturating_sub(window),
+            None => 0
+        };
+        &self.history[start..]
+    }
+
+    // Let go of states older than the window. They're dropped in bulk once the history has doubled
+    // so each tick only costs a move on average.
+    fn forget(&mut self, window: Option<usize>) {
+        if let Some(window) = window {
+            if self.history.len() >= 2 * window {
+                let old = self.history.len() - window;
+                self.history.drain(..old);
+            }
+        }
+    }
+}
+
+// One of the populations sharing a world, evolved by its own creator and scored by its own fitness
+pub struct Team {
+    pub creator: Creator,
+    fitness: Box<Fitness>,
+    // Where the team's actors start, the config's spawn when None
+    pub spawn: Option<Spawn>,
+    // The team this one is up against. Its actors sense the nearest rival still alive, which takes
+    // two more network inputs.
+    pub rival: Option<usize>,
+    // Whether touching a rival catches it
+    pub catches: bool,
+    // Hall of fame members that join every generation without evolving, as opponents the rival
+    // has to keep beating
+    pub veterans: usize
+}
+
+impl Team {
+    pub fn new(creator: Creator, fitness: Box<Fitness>) -> Team {
+        Team {
+            creator: creator,
+            fitness: fitness,
+            spawn: None,
+            rival: None,
+            catches: false,
+            veterans: 0
+        }
+    }
+
+    pub fn fitness(&self) -> &Fitness {
+        &*self.fitness
+    }
 
-        self.acceleration = self.acceleration + jerk * SPT;
-        self.velocity = self.velocity + self.acceleration * SPT;
-        self.position = self.position + self.velocity * SPT;
-        self.genome.fitness = fitness(&self);
+    // Network inputs the team's actors need in a world set up by `config`
+    pub fn num_inputs(&self, config: &WorldConfig) -> u64 {
+        config.num_inputs() + if self.rival.is_some() { coevolution::RIVAL_INPUTS } else { 0 }
     }
 }
 
 pub struct World {
     pub actors: Vec<Actor>,
-    pub creator: Creator,
-    pub generation_tick: u64
+    // Every actor plays for one of these, usually there's only the one
+    pub teams: Vec<Team>,
+    pub config: WorldConfig,
+    // Reset from the config every generation
+    pub entities: Vec<Entity>,
+    pub generation_tick: u64,
+    // Combined tick scores of each actor over the evaluation window
+    totals: Vec<RunningTotal>,
+    // Time passed to `step` that hasn't been simulated yet
+    accumulator: f64,
+    // Ticks since the world was made
+    ticks: u64
 }
 
 impl World {
     pub fn new() -> World {
-        let mut actors = Vec::with_capacity(100);
-        for _ in 0..100 {
-            let genome = Genome::random(7, 2);
-            let actor = Actor::new(genome);
-            actors.push(actor);
+        World::with_config(WorldConfig::new(), Creator::new())
+    }
+
+    // A single team, scored on ending up as close to (0, 0) as possible
+    pub fn with_config(config: WorldConfig, creator: Creator) -> World {
+        let fitness = Box::new(ReachTarget { target: Pnt2::new(0f64, 0f64), total: Total::Last });
+        World::with_teams(config, vec![Team::new(creator, fitness)])
+    }
+
+    // Every team starts with `config.population` random genomes
+    pub fn with_teams(config: WorldConfig, teams: Vec<Team>) -> World {
+        let mut teams = teams;
+        let genomes = teams.iter_mut().map(|team| {
+            let inputs = team.num_inputs(&config);
+            team.creator.random_genomes(config.population, inputs, ACTOR_OUTPUTS)
+        }).collect();
+        let ticks = config.ticks_per_generation();
+        let mut world = World {
+            actors: Vec::new(),
+            teams: teams,
+            entities: Vec::new(),
+            config: config,
+            generation_tick: ticks,
+            totals: Vec::new(),
+            accumulator: 0f64,
+            ticks: 0
+        };
+        world.spawn(genomes);
+        world
+    }
+
+    // Creator of the first team
+    pub fn creator(&self) -> &Creator {
+        &self.teams[0].creator
+    }
+
+    // Score the first team with `fitness` from now on
+    pub fn set_fitness(&mut self, fitness: Box<Fitness>) {
+        self.set_team_fitness(0, fitness);
+    }
+
+    // Score `team` with `fitness` from now on. Its actors' tick scores so far are combined the old
+    // way, so they start over, other teams keep theirs.
+    pub fn set_team_fitness(&mut self, team: usize, fitness: Box<Fitness>) {
+        self.teams[team].fitness = fitness;
+        for i in 0..self.actors.len() {
+            if self.actors[i].team == team {
+                self.totals[i] = self.running_total(team);
+            }
+        }
+    }
+
+    // Fitness of the first team
+    pub fn fitness(&self) -> &Fitness {
+        self.teams[0].fitness()
+    }
+
+    // Replace the actors with new ones made from each team's genomes, joined by the team's
+    // veterans, in a fresh world
+    fn spawn(&mut self, genomes: Vec<Vec<Genome>>) {
+        self.entities = self.config.entities.clone();
+        self.actors.clear();
+        for (team, genomes) in genomes.into_iter().enumerate() {
+            let evolving = genomes.len();
+            let mut genomes = genomes;
+            let count = self.teams[team].veterans;
+            let creator = &mut self.teams[team].creator;
+            genomes.extend((0..count).filter_map(|_|
+                creator.hall_of_fame.sample(&mut creator.rng).map(|entry| entry.genome.clone())));
+
+            for (i, genome) in genomes.into_iter().enumerate() {
+                let mut actor = self.newborn(genome, team, i);
+                actor.veteran = i >= evolving;
+                self.actors.push(actor);
+            }
         }
+        self.totals = (0..self.actors.len()).map(|i| self.running_total(self.actors[i].team)).collect();
+    }
+
+    fn running_total(&self, team: usize) -> RunningTotal {
+        RunningTotal::windowed(self.teams[team].fitness.total(), self.config.evaluation_window())
+    }
 
-        World {
-            actors: actors,
-            creator: Creator::new(),
-            generation_tick: TPG
+    // Fresh actor for the `index`th place of its team
+    fn newborn(&mut self, genome: Genome, team: usize, index: usize) -> Actor {
+        let position = {
+            let team = &mut self.teams[team];
+            let spawn = team.spawn.as_ref().unwrap_or(&self.config.spawn);
+            spawn.point(index, &self.config, &mut team.creator.rng)
+        };
+        let mut actor = Actor::new(genome, position);
+        actor.team = team;
+        if let Some(ref energy) = self.config.energy {
+            actor.energy = energy.initial;
         }
+        actor
     }
 
-    pub fn update(&mut self) {
+    // Indices of the team's actors that are evolving, veterans aren't
+    fn members(&self, team: usize) -> Vec<usize> {
+        (0..self.actors.len())
+            .filter(|&i| self.actors[i].team == team && !self.actors[i].veteran)
+            .collect()
+    }
+
+    // Fitness of every actor from its life so far, or the evaluation window of it
+    fn score_lifetimes(&mut self) {
+        let window = self.config.evaluation_window();
+        for (actor, total) in self.actors.iter_mut().zip(self.totals.iter()) {
+            let fitness = &self.teams[actor.team].fitness;
+            actor.genome.fitness = fitness.score_lifetime(actor.recent(window), total.value(), &self.config);
+        }
+    }
+
+    // Swap the team's worst actor that's had time to prove itself, or has died, for a new one
+    fn replace_worst(&mut self, team: usize, min_age: u64) {
+        let members = self.members(team);
+        let eligible: Vec<bool> = members.iter()
+            .map(|&i| !self.actors[i].alive || self.actors[i].age >= min_age)
+            .collect();
+
+        let replacement = {
+            let actors = &self.actors;
+            let genomes: Vec<&Genome> = members.iter().map(|&i| &actors[i].genome).collect();
+            self.teams[team].creator.replace_worst(&genomes, &eligible)
+        };
+        if let Some((k, child)) = replacement {
+            let i = members[k];
+            self.actors[i] = self.newborn(child, team, k);
+            self.totals[i] = self.running_total(team);
+        }
+    }
+
+    // Evolving genomes of the first team
+    pub fn genomes(&self) -> Vec<&Genome> {
+        self.team_genomes(0)
+    }
+
+    pub fn team_genomes(&self, team: usize) -> Vec<&Genome> {
+        self.members(team).into_iter().map(|i| &self.actors[i].genome).collect()
+    }
+
+    // Run as many ticks as fit into the time passed since the last step, carrying the rest over.
+    // However `dt` is split up the same ticks happen, so a run only depends on the total time.
+    // Returns the stats of the generations finished on the way.
+    pub fn step(&mut self, dt: f64) -> Vec<GenerationStats> {
+        let mut finished = Vec::new();
+        self.accumulator += dt;
+        // Leeway for rounding errors when steps are exactly a tick
+        while self.accumulator >= SPT - 1e-9 {
+            self.accumulator -= SPT;
+            if let Some(stats) = self.update() {
+                finished.push(stats);
+            }
+        }
+        finished
+    }
+
+    // A single tick. Returns the first team's stats of the finished generation when a new one is
+    // started, every team's creator keeps a history of its own.
+    pub fn update(&mut self) -> Option<GenerationStats> {
+        let mut finished = None;
+        self.tick();
+
+        self.ticks += 1;
         self.generation_tick -= 1;
+        match self.config.replacement {
+            Replacement::SteadyState { interval, min_age } => {
+                if self.ticks % interval.max(1) == 0 {
+                    self.score_lifetimes();
+                    for team in 0..self.teams.len() {
+                        self.replace_worst(team, min_age);
+                    }
+                }
 
-        if self.generation_tick <= 0 {
-            self.generation_tick = TPG;
+                // Nothing is reset, generations are only how often stats come out. Newborns
+                // haven't earned a fitness yet so they're left out.
+                if self.generation_tick <= 0 {
+                    self.generation_tick = self.config.ticks_per_generation();
+                    self.score_lifetimes();
+                    for team in 0..self.teams.len() {
+                        let members = self.members(team);
+                        let actors = &self.actors;
+                        let mut judged: Vec<&Genome> = members.iter()
+                            .map(|&i| &actors[i])
+                            .filter(|actor| !actor.alive || actor.age >= min_age)
+                            .map(|actor| &actor.genome)
+                            .collect();
+                        if judged.len() == 0 {
+                            judged = members.iter().map(|&i| &actors[i].genome).collect();
+                        }
+                        let stats = self.teams[team].creator.report(judged);
+                        if team == 0 {
+                            finished = Some(stats);
+                        }
+                    }
+                }
+            },
+            Replacement::Generational => {
+                // Every actor gets exactly the generation's ticks unless it dies, there's no point
+                // going on once everyone has
+                if self.generation_tick <= 0 || self.actors.iter().all(|actor| !actor.alive) {
+                    self.generation_tick = self.config.ticks_per_generation();
+                    self.score_lifetimes();
 
-            // Get next generation from current actor genomes
-            let next_gen = self.creator.next_generation(
-                self.actors.iter().map(|actor| &actor.genome).collect()
-            );
+                    // Get next generation from current actor genomes
+                    let mut next_gen = Vec::with_capacity(self.teams.len());
+                    for team in 0..self.teams.len() {
+                        let members = self.members(team);
+                        let actors = &self.actors;
+                        let (genomes, stats) = self.teams[team].creator.next_generation(
+                            members.iter().map(|&i| &actors[i].genome).collect()
+                        );
+                        if team == 0 {
+                            finished = Some(stats);
+                        }
+                        next_gen.push(genomes);
+                    }
 
-            // Kill your parents
-            self.actors.clear();
+                    // Kill your parents
+                    self.spawn(next_gen);
+                }
+            }
+        }
 
-            // Create actors with new genomes
-            for genome in next_gen {
-                let actor = Actor::new(genome);
-                self.actors.push(actor);
+        finished
+    }
+
+    // Everything that happens in a tick apart from evolving
+    fn tick(&mut self) {
+        // Everyone alive senses the world as it was at the start of the tick
+        let living: Vec<usize> = (0..self.actors.len()).filter(|&i| self.actors[i].alive).collect();
+        let positions: Vec<Pnt2<f64>> = living.iter().map(|&i| self.actors[i].position).collect();
+        let team_positions: Vec<Vec<Pnt2<f64>>> = (0..self.teams.len()).map(|team|
+            living.iter().filter(|&&i| self.actors[i].team == team).map(|&i| self.actors[i].position).collect()
+        ).collect();
+        let inputs: Vec<Vec<f64>> = living.iter().enumerate().map(|(k, &i)| {
+            let actor = &self.actors[i];
+            let mut inputs = actor.sense(&self.config);
+            inputs.extend(sensors::read(&self.config, k, &positions, actor.heading, &self.entities));
+            if let Some(rival) = self.teams[actor.team].rival {
+                inputs.extend(coevolution::sense_rival(actor, &team_positions[rival], &self.config));
+            }
+            inputs
+        }).collect();
+
+        entities::advance(&mut self.entities, &self.config);
+        entities::respawn(&mut self.entities, &self.config, &mut self.teams[0].creator.rng);
+        let mut events = Vec::with_capacity(living.len());
+        let mut gathered = Vec::with_capacity(living.len());
+        for (&i, inputs) in living.iter().zip(inputs) {
+            let actor = &mut self.actors[i];
+            for &field in self.config.physics.fields.iter() {
+                actor.push(field);
+            }
+            actor.control(inputs, &self.config);
+            boundary::confine(actor, ACTOR_RADIUS, &self.config);
+            let actor_events = entities::interact(actor, ACTOR_RADIUS, &mut self.entities, &self.config);
+            gathered.push(match self.config.energy {
+                Some(ref energy) => energy::metabolise(actor, energy, &actor_events),
+                None => 0f64
+            });
+            events.push(actor_events);
+        }
+
+        // Encounters once everyone has moved
+        for (team, catchers) in self.teams.iter().enumerate() {
+            if let (true, Some(rival)) = (catchers.catches, catchers.rival) {
+                coevolution::catch(&mut self.actors, &living, team, rival, &mut events, &self.config);
             }
         }
 
-        for actor in self.actors.iter_mut() {
-            actor.update();
+        for ((&i, events), gathered) in living.iter().zip(events).zip(gathered) {
+            let actor = &mut self.actors[i];
+            actor.record(events, gathered);
+            if let Some(score) = self.teams[actor.team].fitness.score_tick(&actor.history, &self.config) {
+                self.totals[i].add(score);
+            }
+            actor.forget(self.config.evaluation_window());
         }
     }
 }
 
-fn fitness(actor: &Actor) -> f64 {
-    -actor.position.dist(&Pnt2::new(0f64, 0f64))
+impl Evolve for World {
+    fn generation(&mut self) -> GenerationStats {
+        loop {
+            if let Some(stats) = self.update() {
+                return stats;
+            }
+        }
+    }
+
+    fn champion(&self) -> Option<&Genome> {
+        self.creator().hall_of_fame.best().map(|entry| &entry.genome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::{Control, Replacement, WorldConfig};
+    use energy::Energy;
+    use entities::{Entity, Event};
+    use fitness::{Fitness, State, TimeSurvived, Total};
+    use neat;
+    use neat::Creator;
+    use neat::config::Config;
+    use neat::control::Evolve;
+    use neat::genetics::{Gene, Genome};
+
+    use na::{Pnt2, Vec2};
+
+    use std::f64::consts::PI;
+
+    fn seeded_creator() -> Creator {
+        let mut config = Config::new();
+        config.seed = Some(1);
+        Creator::with_config(config)
+    }
+
+    // Number of pellets eaten
+    struct Eaten;
+
+    impl Fitness for Eaten {
+        fn score_lifetime(&self, history: &[State], _: f64, _: &WorldConfig) -> f64 {
+            history.iter().flat_map(|state| state.events.iter())
+                .filter(|event| match **event { Event::Ate(_) => true, _ => false })
+                .count() as f64
+        }
+    }
+
+    // One point for every tick alive
+    struct Ticks;
+
+    impl Fitness for Ticks {
+        fn score_tick(&self, _: &[State], _: &WorldConfig) -> Option<f64> {
+            Some(1f64)
+        }
+    }
+
+    // Drives the first output to nearly 1 from the bias, the second stays 0
+    fn full_first_output() -> Actor {
+        let gene = Gene { into: 6, out: 7, weight: 10f64, enabled: true, innovation: 0 };
+        Actor::new(Genome::new(vec![gene], 7, 2), Pnt2::new(0f64, 0f64))
+    }
+
+    fn world(population: usize) -> World {
+        let mut config = WorldConfig::new();
+        config.population = population;
+        config.entities = vec![Entity::food(config.center(), 2f64, 1000)];
+        World::with_config(config, seeded_creator())
+    }
+
+    #[test]
+    fn world_scores_lifetimes_with_fitness_function() {
+        let mut world = world(10);
+        world.set_fitness(Box::new(TimeSurvived));
+
+        let stats = world.generation();
+        assert!(stats.min_fitness == world.config.generation_seconds);
+        assert!(world.actors.iter().all(|actor| actor.history.len() == 0));
+    }
+
+    #[test]
+    fn replaced_fitness_combines_scores_its_own_way_from_the_start() {
+        let mut world = world(3);
+        world.set_fitness(Box::new(Ticks));
+        assert!(world.fitness().total() == Total::Sum);
+
+        let stats = world.generation();
+        assert!(stats.generation == 1);
+        assert!(stats.min_fitness == world.config.ticks_per_generation() as f64);
+    }
+
+    #[test]
+    fn replacing_a_teams_fitness_leaves_other_teams_totals() {
+        let mut config = WorldConfig::new();
+        config.population = 2;
+        let teams = (0..2).map(|_| Team::new(seeded_creator(), Box::new(Ticks))).collect();
+        let mut world = World::with_teams(config, teams);
+        for _ in 0..5 {
+            world.tick();
+        }
+
+        world.set_team_fitness(1, Box::new(Ticks));
+        for (actor, total) in world.actors.iter().zip(world.totals.iter()) {
+            assert!(total.value() == if actor.team == 0 { 5f64 } else { 0f64 });
+        }
+    }
+
+    #[test]
+    fn fitness_sees_entity_events() {
+        let mut world = world(3);
+        world.set_fitness(Box::new(Eaten));
+
+        // Everyone starts on the pellet but only the first actor gets it
+        let stats = world.generation();
+        assert!(stats.max_fitness == 1f64);
+        assert!(stats.mean_fitness == 1f64 / 3f64);
+        assert!(world.entities[0].present() && world.entities[0].position == Pnt2::new(320f64, 240f64));
+    }
+
+    #[test]
+    fn actors_out_of_energy_die_early() {
+        let mut config = WorldConfig::new();
+        config.population = 5;
+        let mut energy = Energy::new();
+        energy.initial = 1f64;
+        energy.metabolism = 10f64;
+        config.energy = Some(energy);
+        let mut world = World::with_config(config, seeded_creator());
+        world.set_fitness(Box::new(TimeSurvived));
+
+        // A tenth of a second to live, paid for at the end of each tick
+        let stats = world.generation();
+        assert!(stats.max_fitness == 3f64 * SPT);
+        assert!(world.generation_tick == world.config.ticks_per_generation());
+    }
+
+    #[test]
+    fn steady_state_replaces_actors_one_at_a_time() {
+        let mut config = WorldConfig::new();
+        config.population = 10;
+        config.generation_seconds = 1f64;
+        config.replacement = Replacement::SteadyState { interval: 5, min_age: 10 };
+        let mut world = World::with_config(config, seeded_creator());
+
+        // Replacements on ticks 10, 15, 20 and 25, the world carries on after the stats
+        let stats = world.generation();
+        assert!(stats.generation == 1);
+        let survivors = world.actors.iter().filter(|actor| actor.age == 25).count();
+        assert!(survivors >= 6 && survivors < 10);
+        assert!(world.actors.len() == 10);
+    }
+
+    #[test]
+    fn steady_state_judges_everyone_on_the_same_window() {
+        let mut config = WorldConfig::new();
+        config.population = 10;
+        config.generation_seconds = 1f64;
+        config.replacement = Replacement::SteadyState { interval: 5, min_age: 10 };
+        let mut world = World::with_config(config, seeded_creator());
+        world.set_fitness(Box::new(Ticks));
+
+        // Veterans outlive several windows but don't score more for it
+        for _ in 0..6 {
+            let stats = world.generation();
+            assert!(stats.min_fitness == 10f64 && stats.max_fitness == 10f64);
+            assert!(world.actors.iter().all(|actor| actor.history.len() < 20));
+        }
+        assert!(world.actors.iter().any(|actor| actor.age > 20));
+        assert!(world.actors.iter().all(|actor|
+            actor.history.last().map_or(true, |state| state.tick == actor.age - 1)));
+    }
+
+    #[test]
+    fn control_models_turn_and_move_actors() {
+        let inputs = vec![0f64, 0f64, 0f64, 0f64, 0f64, 0f64, 1f64];
+        let mut config = WorldConfig::new();
+
+        // Full thrust straight down
+        let mut thruster = full_first_output();
+        thruster.heading = PI / 2f64;
+        config.control = Control::ThrustTurn { max_thrust: 10f64, max_turn: 1f64 };
+        thruster.control(inputs.clone(), &config);
+        assert!(thruster.heading == PI / 2f64 && thruster.angular_velocity == 0f64);
+        assert!(thruster.velocity.x.abs() < 1e-9 && thruster.velocity.y > 0f64);
+
+        // Only the left wheel turns so it swings clockwise on screen
+        let mut driven = full_first_output();
+        config.control = Control::DifferentialDrive { wheel_base: 10f64, max_speed: 20f64 };
+        driven.control(inputs, &config);
+        assert!(driven.angular_velocity > 1.9f64 && driven.angular_velocity <= 2f64);
+        assert!(driven.heading > 0f64 && driven.heading < 0.1f64);
+        assert!(driven.velocity.x > 0f64);
+    }
+
+    #[test]
+    fn pushed_forces_last_one_tick() {
+        let inputs = vec![0f64; 7];
+        let mut config = WorldConfig::new();
+        config.physics.drag = 0f64;
+        let mut actor = Actor::new(Genome::new(Vec::new(), 7, 2), Pnt2::new(0f64, 0f64));
+
+        actor.push(Vec2::new(0f64, 60f64));
+        actor.control(inputs.clone(), &config);
+        assert!(actor.velocity == Vec2::new(0f64, 60f64 * SPT));
+        assert!(actor.acceleration == Vec2::new(0f64, 60f64));
+
+        // Coasting afterwards
+        actor.control(inputs, &config);
+        assert!(actor.velocity == Vec2::new(0f64, 60f64 * SPT));
+        assert!(actor.acceleration == Vec2::new(0f64, 0f64));
+    }
+
+    #[test]
+    fn stepping_is_independent_of_frame_rate() {
+        // 1.6 seconds in steps of `dt`, 40 ticks over one and a half generations
+        let run = |steps: usize, dt: f64| {
+            let mut config = WorldConfig::new();
+            config.population = 10;
+            config.generation_seconds = 1f64;
+            let mut neat_config = neat::config::Config::new();
+            neat_config.seed = Some(7);
+            let mut world = World::with_config(config, Creator::with_config(neat_config));
+
+            let mut stats = Vec::new();
+            for _ in 0..steps {
+                stats.extend(world.step(dt));
+            }
+            let fitness: Vec<f64> = stats.iter().map(|stats| stats.max_fitness).collect();
+            let positions: Vec<Pnt2<f64>> = world.actors.iter().map(|actor| actor.position).collect();
+            (fitness, positions)
+        };
+
+        // Real time in the viewer and all at once
+        let (fitness, positions) = run(96, 1f64 / 60f64);
+        assert!(fitness.len() == 1);
+        assert!((fitness, positions) == run(1, 96f64 / 60f64));
+    }
 }
@@ -0,0 +1,264 @@
+// What actors perceive of the world around them. Each sensor adds one network input.
+
+use ACTOR_RADIUS;
+use boundary::{self, Boundary};
+use config::WorldConfig;
+use entities::{Entity, Kind, Shape};
+
+use na::{Pnt2, Vec2};
+
+use std::f64;
+use std::f64::consts::PI;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Target {
+    Food,
+    Goal
+}
+
+// Angles are in radians clockwise from the actor's heading, as the world is drawn with y pointing
+// down
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sensor {
+    // Distance along a ray to the nearest obstacle, wall or other actor, scaled so 1.0 means
+    // nothing within `length`
+    Rangefinder { angle: f64, length: f64 },
+    // Closeness of the nearest target whose direction lies between the two angles, 0.0 when
+    // there's none within `range` and 1.0 when it's right on top of the actor
+    Radar { from: f64, to: f64, range: f64, target: Target }
+}
+
+impl Sensor {
+    // `count` rays spread evenly around the actor
+    pub fn rangefinders(count: usize, length: f64) -> Vec<Sensor> {
+        (0..count).map(|i| Sensor::Rangefinder {
+            angle: 2f64 * PI * i as f64 / count as f64,
+            length: length
+        }).collect()
+    }
+
+    // The circle around the actor cut into `count` equal slices
+    pub fn radar(count: usize, range: f64, target: Target) -> Vec<Sensor> {
+        (0..count).map(|i| Sensor::Radar {
+            from: 2f64 * PI * i as f64 / count as f64,
+            to: 2f64 * PI * (i + 1) as f64 / count as f64,
+            range: range,
+            target: target
+        }).collect()
+    }
+}
+
+// Readings of every configured sensor for the `index`th of the actors at `positions`, which is
+// facing `heading`
+pub fn read(config: &WorldConfig, index: usize, positions: &[Pnt2<f64>], heading: f64,
+            entities: &[Entity]) -> Vec<f64> {
+    let position = positions[index];
+    config.sensors.iter().map(|sensor| match *sensor {
+        Sensor::Rangefinder { angle, length } => {
+            let angle = angle + heading;
+            let direction = Vec2::new(angle.cos(), angle.sin());
+            let mut nearest = if config.boundary.walled() {
+                wall_distance(position, direction, config)
+            } else {
+                f64::INFINITY
+            };
+
+            for entity in entities.iter() {
+                if let Kind::Obstacle(shape) = entity.kind {
+                    for center in images(position, entity.position, config) {
+                        let hit = match shape {
+                            Shape::Circle => ray_circle(position, direction, center, entity.radius),
+                            Shape::Rectangle { half_width, half_height } =>
+                                ray_rectangle(position, direction, center, half_width, half_height)
+                        };
+                        nearest = nearest.min(hit.unwrap_or(f64::INFINITY));
+                    }
+                }
+            }
+
+            for (j, other) in positions.iter().enumerate() {
+                if j != index {
+                    for center in images(position, *other, config) {
+                        let hit = ray_circle(position, direction, center, ACTOR_RADIUS);
+                        nearest = nearest.min(hit.unwrap_or(f64::INFINITY));
+                    }
+                }
+            }
+
+            (nearest / length).min(1f64)
+        },
+        Sensor::Radar { from, to, range, target } => {
+            entities.iter()
+                .filter(|entity| entity.present() && match (entity.kind, target) {
+                    (Kind::Food { .. }, Target::Food) | (Kind::Goal, Target::Goal) => true,
+                    _ => false
+                })
+                .filter_map(|entity| {
+                    let offset = boundary::offset(position, entity.position, config);
+                    let distance = (offset.x * offset.x + offset.y * offset.y).sqrt();
+                    if distance <= range && within(offset.y.atan2(offset.x) - heading, from, to) {
+                        Some(1f64 - distance / range)
+                    } else {
+                        None
+                    }
+                })
+                .fold(0f64, |acc, closeness| acc.max(closeness))
+        }
+    }).collect()
+}
+
+// Whether `angle` lies in the slice going clockwise from `from` to `to`. A slice whose ends meet
+// after a whole turn covers everything.
+fn within(angle: f64, from: f64, to: f64) -> bool {
+    let width = wrap_angle(to - from);
+    (width == 0f64 && to != from) || wrap_angle(angle - from) < width
+}
+
+// Same angle in 0..2π
+pub fn wrap_angle(angle: f64) -> f64 {
+    let turn = 2f64 * PI;
+    let wrapped = angle % turn;
+    if wrapped < 0f64 { wrapped + turn } else { wrapped }
+}
+
+// Everywhere a ray from `from` can find something at `at`. When the world wraps around that's the
+// nearest copy and the copies a world away from it.
+fn images(from: Pnt2<f64>, at: Pnt2<f64>, config: &WorldConfig) -> Vec<Pnt2<f64>> {
+    let nearest = from + boundary::offset(from, at, config);
+    match config.boundary {
+        Boundary::Wrap => {
+            let mut images = Vec::with_capacity(9);
+            for &dx in [-config.width, 0f64, config.width].iter() {
+                for &dy in [-config.height, 0f64, config.height].iter() {
+                    images.push(nearest + Vec2::new(dx, dy));
+                }
+            }
+            images
+        },
+        _ => vec![nearest]
+    }
+}
+
+// Distance to the walls around the world
+fn wall_distance(position: Pnt2<f64>, direction: Vec2<f64>, config: &WorldConfig) -> f64 {
+    let along = |p: f64, d: f64, size: f64| {
+        if d > 0f64 {
+            (size - p) / d
+        } else if d < 0f64 {
+            -p / d
+        } else {
+            f64::INFINITY
+        }
+    };
+    along(position.x, direction.x, config.width)
+        .min(along(position.y, direction.y, config.height))
+        .max(0f64)
+}
+
+fn ray_circle(origin: Pnt2<f64>, direction: Vec2<f64>, center: Pnt2<f64>, radius: f64) -> Option<f64> {
+    let offset = origin - center;
+    let b = offset.x * direction.x + offset.y * direction.y;
+    let c = offset.x * offset.x + offset.y * offset.y - radius * radius;
+    if c <= 0f64 {
+        // Already inside
+        return Some(0f64);
+    }
+
+    let discriminant = b * b - c;
+    if discriminant < 0f64 {
+        return None;
+    }
+    let t = -b - discriminant.sqrt();
+    if t >= 0f64 { Some(t) } else { None }
+}
+
+fn ray_rectangle(origin: Pnt2<f64>, direction: Vec2<f64>, center: Pnt2<f64>,
+                 half_width: f64, half_height: f64) -> Option<f64> {
+    let mut near = f64::NEG_INFINITY;
+    let mut far = f64::INFINITY;
+
+    let slabs = [(origin.x, direction.x, center.x, half_width),
+                 (origin.y, direction.y, center.y, half_height)];
+    for &(p, d, c, half) in slabs.iter() {
+        if d == 0f64 {
+            if p < c - half || p > c + half {
+                return None;
+            }
+            continue;
+        }
+        let t1 = (c - half - p) / d;
+        let t2 = (c + half - p) / d;
+        near = near.max(t1.min(t2));
+        far = far.min(t1.max(t2));
+    }
+
+    if far < near.max(0f64) {
+        return None;
+    }
+    Some(near.max(0f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::WorldConfig;
+    use entities::Entity;
+
+    use na::Pnt2;
+
+    use std::f64::consts::PI;
+
+    #[test]
+    fn rangefinders_see_walls_obstacles_and_actors() {
+        let mut config = WorldConfig::new();
+        config.width = 100f64;
+        config.height = 100f64;
+        config.sensors = Sensor::rangefinders(4, 50f64);
+        let entities = vec![Entity::rectangle(Pnt2::new(70f64, 50f64), 10f64, 10f64)];
+        let positions = vec![Pnt2::new(50f64, 50f64), Pnt2::new(50f64, 30f64)];
+
+        // Right: box at 15, down: wall at 50, left: wall at 50, up: actor at 15
+        let readings = read(&config, 0, &positions, 0f64, &entities);
+        assert!(readings == vec![0.3, 1.0, 1.0, 0.3]);
+        assert!(config.num_inputs() == 11);
+
+        // Without walls the box is also seen round the back
+        config.boundary = Boundary::Wrap;
+        config.sensors = Sensor::rangefinders(2, 100f64);
+        let readings = read(&config, 0, &positions, 0f64, &entities);
+        assert!(readings == vec![0.15, 0.75]);
+    }
+
+    #[test]
+    fn radar_finds_targets_in_slice() {
+        let mut config = WorldConfig::new();
+        config.sensors = Sensor::radar(4, 100f64, Target::Food);
+        let entities = vec![
+            Entity::food(Pnt2::new(150f64, 100f64), 2f64, 10),
+            Entity::goal(Pnt2::new(100f64, 50f64), 2f64)
+        ];
+
+        let readings = read(&config, 0, &[Pnt2::new(100f64, 100f64)], 0f64, &entities);
+        assert!(readings == vec![0.5, 0.0, 0.0, 0.0]);
+
+        // Facing down the food is on the left
+        let readings = read(&config, 0, &[Pnt2::new(100f64, 100f64)], PI / 2f64, &entities);
+        assert!(readings == vec![0.0, 0.0, 0.0, 0.5]);
+        assert!(within(-PI / 2f64, 3f64 * PI / 2f64, 2f64 * PI));
+    }
+
+    #[test]
+    fn full_circle_radar_sees_everywhere() {
+        let mut config = WorldConfig::new();
+        config.sensors = Sensor::radar(1, 100f64, Target::Food);
+        let entities = vec![Entity::food(Pnt2::new(100f64, 50f64), 2f64, 10)];
+
+        let readings = read(&config, 0, &[Pnt2::new(100f64, 100f64)], 0f64, &entities);
+        assert!(readings == vec![0.5]);
+        for &angle in [0f64, PI / 3f64, PI, 5f64 * PI / 3f64, -PI / 4f64].iter() {
+            assert!(within(angle, 0f64, 2f64 * PI));
+        }
+        // An empty slice sees nothing
+        assert!(!within(0f64, 1f64, 1f64));
+    }
+}
@@ -1,7 +1,74 @@
 #[macro_use]
 extern crate log;
+extern crate rise;
 
 
 #[cfg(test)]
 mod tests {
+    use rise::neat;
+    use rise::neat::Creator;
+    use rise::neat::benchmarks::{self, Xor};
+    use rise::neat::config::Config;
+    use rise::neat::distributed::{self, Coordinator};
+    use rise::neat::genetics::Genome;
+    use rise::neat::population::Population;
+    use rise::neat::task::Task;
+
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn xor_is_solved() {
+        let mut config = Config::new();
+        config.seed = Some(2);
+        let mut xor = Xor;
+        let mut population = Population::new(&xor, 150, Creator::with_config(config));
+        let result = benchmarks::run(&mut xor, &mut population, 150);
+
+        info!("XOR: {:?}", result);
+        assert!(result.solved_in.is_some());
+        // Every generation went through the population's own steps, stats and all
+        assert!(population.creator.history.len() as u64 == result.solved_in.unwrap());
+    }
+
+    #[test]
+    fn workers_on_localhost_share_evaluation() {
+        let mut coordinator = Coordinator::bind("127.0.0.1:0", Duration::from_secs(5)).unwrap();
+        let addr = coordinator.local_addr();
+        let workers: Vec<_> = (0..3)
+            .map(|_| thread::spawn(move || distributed::work(addr, &mut Xor).unwrap()))
+            .collect();
+
+        let mut rng = neat::seeded_rng(1);
+        let mut genomes: Vec<Genome> = (0..30).map(|_| Genome::random(3, 1, &mut rng)).collect();
+        coordinator.evaluate(&mut genomes).unwrap();
+        for genome in genomes.iter() {
+            assert!(genome.fitness == Xor.evaluate(&genome.network).fitness);
+        }
+
+        // Workers finish once the coordinator hangs up
+        drop(coordinator);
+        let jobs: u64 = workers.into_iter().map(|worker| worker.join().unwrap()).sum();
+        assert!(jobs == 30);
+    }
+
+    #[test]
+    fn same_seed_gives_same_population() {
+        let run = |seed| {
+            let mut config = Config::new();
+            config.seed = Some(seed);
+            let mut xor = Xor;
+            let mut population = Population::new(&xor, 50, Creator::with_config(config));
+            population.run(&mut xor, 10);
+
+            let mut text = Vec::new();
+            for genome in population.genomes.iter() {
+                genome.write(&mut text).unwrap();
+            }
+            text
+        };
+
+        assert!(run(5) == run(5));
+        assert!(run(5) != run(6));
+    }
 }